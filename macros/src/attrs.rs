@@ -0,0 +1,398 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use proc_macro2::Span;
+use syn::{
+    ext::IdentExt, parenthesized, parse::ParseStream, punctuated::Punctuated, spanned::Spanned,
+    Attribute, Error, Expr, ExprRange, Ident, LitBool, LitStr, Path, RangeLimits, Token,
+    Visibility,
+};
+
+/// The options given in a field's `#[sync(...)]` attributes.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    /// Whether the field has any `#[sync]` attribute at all.
+    pub(crate) sync: bool,
+    /// `#[sync(name = "...")]` overrides the name the message variant and
+    /// control point get from the field.
+    pub(crate) name: Option<LitStr>,
+    /// `#[sync(nested)]` marks a field whose type derives `Synchronization`
+    /// too, so that its own messages are routed to it.
+    pub(crate) nested: bool,
+    /// `#[sync(outbound)]` marks the field that `set_and_propagate_*` methods
+    /// send their messages through. It isn't synchronized itself.
+    pub(crate) outbound: Option<Span>,
+    /// `#[sync(on_change = "method")]` names what the generated setter calls
+    /// after storing a new value, overriding the container's `on_change`.
+    pub(crate) on_change: Option<LitStr>,
+    /// `#[sync(tolerance = ...)]` has `diff()` treat values within this
+    /// distance as unchanged, comparing with `struct_sync::ApproxEq`.
+    pub(crate) tolerance: Option<Expr>,
+    /// `#[sync(control = false)]` keeps a field from becoming a control point,
+    /// and `#[sync(control = true)]` makes one of a field whose type doesn't
+    /// look like it could be.
+    pub(crate) control: Option<LitBool>,
+    /// `#[sync(range = lo..=hi)]` bounds the values the field accepts, as
+    /// `(lo, hi)`.
+    pub(crate) range: Option<(Expr, Expr)>,
+    /// `#[sync(validate = "path")]` names a `fn(&T) -> bool` that accepts or
+    /// rejects new values.
+    pub(crate) validate: Option<LitStr>,
+    /// `#[sync(merge)]` marks a field whose type implements
+    /// `struct_sync::Merge`, such as an `OrSet`, so that CRDT merges combine
+    /// its values instead of picking the newer one.
+    pub(crate) merge: Option<Span>,
+    /// `#[sync(min = .., max = ..)]` are the real values that a control
+    /// value's 0.0 and 1.0 map to, and `#[sync(curve = "..", step = ..)]`
+    /// shape the mapping in between.
+    pub(crate) min: Option<Expr>,
+    pub(crate) max: Option<Expr>,
+    pub(crate) curve: Option<LitStr>,
+    pub(crate) step: Option<Expr>,
+    /// `#[sync(display_name = "...")]`, `#[sync(unit = "...")]`,
+    /// `#[sync(default = ..)]` and `#[sync(hidden)]` describe the control
+    /// point in `Synchronized::PARAMS`.
+    pub(crate) display_name: Option<LitStr>,
+    pub(crate) unit: Option<LitStr>,
+    pub(crate) default: Option<Expr>,
+    pub(crate) hidden: Option<Span>,
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut r = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("sync")) {
+            r.sync = true;
+            if attr.tokens.is_empty() {
+                continue;
+            }
+            attr.parse_args_with(|input: ParseStream| {
+                parse_comma_separated(input, |key, input| {
+                    if key == "name" {
+                        set_once(&mut r.name, key, parse_value(input)?)
+                    } else if key == "nested" {
+                        set_flag(&mut r.nested, key)
+                    } else if key == "outbound" {
+                        set_once(&mut r.outbound, key, key.span())
+                    } else if key == "on_change" {
+                        set_once(&mut r.on_change, key, parse_value(input)?)
+                    } else if key == "tolerance" {
+                        set_once(&mut r.tolerance, key, parse_value(input)?)
+                    } else if key == "control" {
+                        set_once(&mut r.control, key, parse_value(input)?)
+                    } else if key == "range" {
+                        set_once(&mut r.range, key, parse_range(input)?)
+                    } else if key == "validate" {
+                        set_once(&mut r.validate, key, parse_value(input)?)
+                    } else if key == "merge" {
+                        set_once(&mut r.merge, key, key.span())
+                    } else if key == "min" {
+                        set_once(&mut r.min, key, parse_value(input)?)
+                    } else if key == "max" {
+                        set_once(&mut r.max, key, parse_value(input)?)
+                    } else if key == "curve" {
+                        let curve: LitStr = parse_value(input)?;
+                        if !["linear", "log", "exp"].contains(&curve.value().as_str()) {
+                            return Err(Error::new(
+                                curve.span(),
+                                "curve is \"linear\", \"log\" or \"exp\"",
+                            ));
+                        }
+                        set_once(&mut r.curve, key, curve)
+                    } else if key == "step" {
+                        set_once(&mut r.step, key, parse_value(input)?)
+                    } else if key == "display_name" {
+                        set_once(&mut r.display_name, key, parse_value(input)?)
+                    } else if key == "unit" {
+                        set_once(&mut r.unit, key, parse_value(input)?)
+                    } else if key == "default" {
+                        set_once(&mut r.default, key, parse_value(input)?)
+                    } else if key == "hidden" {
+                        set_once(&mut r.hidden, key, key.span())
+                    } else {
+                        Err(unknown_attribute(key))
+                    }
+                })
+            })?;
+        }
+        match (&r.min, &r.max) {
+            (Some(given), None) | (None, Some(given)) => {
+                return Err(Error::new_spanned(given, "min and max go together"));
+            }
+            (None, None) => {
+                if let Some(curve) = &r.curve {
+                    return Err(Error::new(curve.span(), "curve needs min and max"));
+                }
+                if let Some(step) = &r.step {
+                    return Err(Error::new_spanned(step, "step needs min and max"));
+                }
+            }
+            (Some(min), Some(max)) => {
+                let log = r.curve.as_ref().is_some_and(|c| c.value() == "log");
+                for bound in [min, max] {
+                    if log && literal_value(bound).is_some_and(|v| v <= 0.0) {
+                        return Err(Error::new_spanned(
+                            bound,
+                            "a log curve spreads over ratios, so min and max must be above 0",
+                        ));
+                    }
+                }
+            }
+        }
+        if r.nested {
+            if let Some(min) = &r.min {
+                return Err(Error::new_spanned(
+                    min,
+                    "nested fields' control points are the child's, so put min and max on the child's fields",
+                ));
+            }
+            if let Some(control) = &r.control {
+                return Err(Error::new(
+                    control.span,
+                    "nested fields' control points are the child's, so `control` doesn't apply",
+                ));
+            }
+            if let Some(span) = r.param_span() {
+                return Err(Error::new(
+                    span,
+                    "nested fields' control points are the child's, so describe them on the child's fields",
+                ));
+            }
+            if let Some(span) = r.merge {
+                return Err(Error::new(
+                    span,
+                    "nested fields already merge field by field, so they can't be #[sync(merge)]",
+                ));
+            }
+            if let Some(tolerance) = &r.tolerance {
+                return Err(Error::new_spanned(
+                    tolerance,
+                    "nested fields are diffed field by field, so put the tolerance on the child's fields",
+                ));
+            }
+            if let Some((lo, _)) = &r.range {
+                return Err(Error::new_spanned(
+                    lo,
+                    "nested fields are checked field by field, so put the range on the child's fields",
+                ));
+            }
+            if let Some(validate) = &r.validate {
+                return Err(Error::new(
+                    validate.span(),
+                    "nested fields are checked field by field, so put the validator on the child's fields",
+                ));
+            }
+        }
+        if let (Some(span), Some(control)) = (r.merge, &r.control) {
+            if control.value {
+                return Err(Error::new(
+                    span,
+                    "#[sync(merge)] fields are merged rather than set, so they can't be control points",
+                ));
+            }
+        }
+        if let Some(span) = r.outbound {
+            if r.name.is_some()
+                || r.nested
+                || r.on_change.is_some()
+                || r.tolerance.is_some()
+                || r.range.is_some()
+                || r.validate.is_some()
+                || r.merge.is_some()
+                || r.min.is_some()
+                || r.control.is_some()
+                || r.param_span().is_some()
+            {
+                return Err(Error::new(
+                    span,
+                    "the #[sync(outbound)] field isn't synchronized, so it takes no other sync attributes",
+                ));
+            }
+        }
+        Ok(r)
+    }
+
+    /// Where the first of the attributes that describe a control point in
+    /// `Synchronized::PARAMS` is, if any is given.
+    pub(crate) fn param_span(&self) -> Option<Span> {
+        self.display_name
+            .as_ref()
+            .map(LitStr::span)
+            .or_else(|| self.unit.as_ref().map(LitStr::span))
+            .or_else(|| self.default.as_ref().map(Spanned::span))
+            .or(self.hidden)
+    }
+}
+
+/// The options given in `#[sync(...)]` attributes on the struct or enum
+/// itself.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    /// `#[sync(accessors)]` generates a getter and a `set_<field>()` setter
+    /// for each `#[sync]` field.
+    pub(crate) accessors: Option<Span>,
+    /// `#[sync(on_change = "method")]` names what generated setters call after
+    /// storing a new value.
+    pub(crate) on_change: Option<LitStr>,
+    /// `#[sync(message = "...")]` names the message enum instead of
+    /// `<Name>Message`.
+    pub(crate) message: Option<Ident>,
+    /// `#[sync(vis = "...")]` sets the visibility of the message enum instead
+    /// of `pub`.
+    pub(crate) vis: Option<Visibility>,
+    pub(crate) vis_span: Option<Span>,
+    /// `#[sync(derive(...))]` adds derives to the message enum.
+    pub(crate) derives: Option<Punctuated<Path, Token![,]>>,
+    /// `#[sync(tolerance = ...)]` is the `diff()` tolerance for `f32` and
+    /// `f64` fields that don't give their own.
+    pub(crate) tolerance: Option<Expr>,
+    /// `#[sync(serde)]` derives `Serialize` and `Deserialize` for the
+    /// generated enums. It needs `struct_sync`'s `serde` feature.
+    pub(crate) serde: Option<Span>,
+}
+
+impl ContainerAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut r = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("sync")) {
+            if attr.tokens.is_empty() {
+                return Err(Error::new_spanned(
+                    attr,
+                    "#[sync] on the struct itself needs options, like #[sync(accessors)]; mark the fields to synchronize with #[sync]",
+                ));
+            }
+            attr.parse_args_with(|input: ParseStream| {
+                parse_comma_separated(input, |key, input| {
+                    if key == "accessors" {
+                        set_once(&mut r.accessors, key, key.span())
+                    } else if key == "on_change" {
+                        set_once(&mut r.on_change, key, parse_value(input)?)
+                    } else if key == "message" {
+                        let name: LitStr = parse_value(input)?;
+                        let name = name.parse().map_err(|_| {
+                            Error::new(
+                                name.span(),
+                                "message takes the name of the enum to generate",
+                            )
+                        })?;
+                        set_once(&mut r.message, key, name)
+                    } else if key == "vis" {
+                        let vis: LitStr = parse_value(input)?;
+                        r.vis_span = Some(vis.span());
+                        let vis = vis.parse().map_err(|_| {
+                            Error::new(vis.span(), "vis takes a visibility, like \"pub(crate)\"")
+                        })?;
+                        set_once(&mut r.vis, key, vis)
+                    } else if key == "derive" {
+                        let content;
+                        parenthesized!(content in input);
+                        let derives = content.parse_terminated(Path::parse_mod_style)?;
+                        set_once(&mut r.derives, key, derives)
+                    } else if key == "tolerance" {
+                        set_once(&mut r.tolerance, key, parse_value(input)?)
+                    } else if key == "serde" {
+                        set_once(&mut r.serde, key, key.span())
+                    } else {
+                        Err(unknown_attribute(key))
+                    }
+                })
+            })?;
+        }
+        if let (None, Some(on_change)) = (r.accessors, &r.on_change) {
+            return Err(Error::new(
+                on_change.span(),
+                "on_change hooks run from generated setters, so they need #[sync(accessors)] on the struct",
+            ));
+        }
+        if let (false, Some(span)) = (cfg!(feature = "serde"), r.serde) {
+            return Err(Error::new(
+                span,
+                "#[sync(serde)] needs struct_sync's `serde` feature",
+            ));
+        }
+        Ok(r)
+    }
+}
+
+/// The value of a numeric literal like `20`, `0.5` or `-1`, or `None` for any
+/// other expression.
+fn literal_value(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            syn::Lit::Int(int) => int.base10_parse().ok(),
+            syn::Lit::Float(float) => float.base10_parse().ok(),
+            _ => None,
+        },
+        Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Neg(_)) => {
+            literal_value(&unary.expr).map(|v| -v)
+        }
+        Expr::Paren(paren) => literal_value(&paren.expr),
+        Expr::Group(group) => literal_value(&group.expr),
+        _ => None,
+    }
+}
+
+/// Parses `key, key = value, ...`, handing each key to `f`, which consumes
+/// whatever follows the key.
+fn parse_comma_separated(
+    input: ParseStream,
+    mut f: impl FnMut(&Ident, ParseStream) -> syn::Result<()>,
+) -> syn::Result<()> {
+    while !input.is_empty() {
+        let key = input.call(Ident::parse_any)?;
+        f(&key, input)?;
+        if input.is_empty() {
+            break;
+        }
+        input.parse::<Token![,]>()?;
+    }
+    Ok(())
+}
+
+/// Parses the `= value` that follows a key.
+fn parse_value<T: syn::parse::Parse>(input: ParseStream) -> syn::Result<T> {
+    input.parse::<Token![=]>()?;
+    input.parse()
+}
+
+/// Parses the `= lo..=hi` that follows `range`. Open and half-open ranges
+/// don't say what to clamp to, so they aren't accepted.
+fn parse_range(input: ParseStream) -> syn::Result<(Expr, Expr)> {
+    match parse_value(input)? {
+        Expr::Range(ExprRange {
+            from: Some(lo),
+            limits: RangeLimits::Closed(_),
+            to: Some(hi),
+            ..
+        }) => Ok((*lo, *hi)),
+        expr => Err(Error::new_spanned(
+            expr,
+            "range takes a closed range, like 0.0..=1.0",
+        )),
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, key: &Ident, value: T) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(Error::new(
+            key.span(),
+            format!("sync attribute `{}` is given more than once", key),
+        ));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+fn set_flag(flag: &mut bool, key: &Ident) -> syn::Result<()> {
+    if *flag {
+        return Err(Error::new(
+            key.span(),
+            format!("sync attribute `{}` is given more than once", key),
+        ));
+    }
+    *flag = true;
+    Ok(())
+}
+
+fn unknown_attribute(key: &Ident) -> Error {
+    Error::new(key.span(), format!("unknown sync attribute `{}`", key))
+}
@@ -1,153 +1,2072 @@
 // Copyright (c) 2023 Mike Tsao. All rights reserved.
 
+use attrs::{ContainerAttrs, FieldAttrs};
 use convert_case::{Case, Casing};
 use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span};
 use quote::{format_ident, quote};
-use syn::Ident;
-use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Fields, Generics};
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, spanned::Spanned, Data, DataEnum, DataStruct,
+    DeriveInput, Error, Expr, Fields, Generics, Ident, Index, LitStr, Member, PathArguments, Type,
+    Visibility,
+};
 
+mod attrs;
+
+/// Generates `<Name>Message`, an enum with one variant that replaces the whole
+/// value and one variant per `#[sync]` field, along with
+/// `struct_sync::Synchronized`, whose `update()` applies those messages, and
+/// `groove_core::traits::Controllable` for the fields that can be set from an
+/// `F32ControlValue`.
+///
+/// The generated code names everything it uses by its full path through
+/// `::struct_sync`, so use the derive as re-exported by `struct_sync`, with no
+/// strum or groove imports needed.
+///
+/// - Named fields are updated through the struct's `set_<field>()` methods;
+///   tuple-struct fields (`Field0`, `Field1`, ...) are assigned directly.
+/// - On enums, `<Name>Message::Kind(<Name>Kind)` switches the active variant,
+///   and fields are addressed as `<Variant><Field>`. Messages for a field of
+///   an inactive variant are ignored.
+/// - `#[sync(name = "...")]` renames a field's variant and control point.
+/// - Fields become control points when their type looks like it converts from
+///   an `F32ControlValue`: containers, strings, arrays and tuples don't.
+///   `#[sync(control = false)]` opts a field out, as for a type alias of a
+///   container, and `#[sync(control = true)]` opts one in.
+/// - `#[sync(nested)]` marks a field whose type derives `Synchronization` too.
+///   Its variant carries the child's `Synchronized::Message`, e.g.,
+///   `Filter(FilterMessage)`, and its control points follow the parent's own,
+///   named like `filter/cutoff`.
+/// - A struct with a `#[sync(outbound)]` field, such as a
+///   `struct_sync::Outbound<<Name>Message>`, gets `set_and_propagate_<field>()`
+///   methods that call `set_<field>()` and then send the matching message
+///   through that field's `send()`.
+/// - `#[sync(accessors)]` on a struct with named fields generates a getter and
+///   a `set_<field>()` setter for each `#[sync]` field. Getters return
+///   containers, strings and nested fields by reference and everything else by
+///   value. `#[sync(on_change = "method")]`, on the struct or on a field, has
+///   the setter call `self.method()` (or `path(self)` for a longer path) after
+///   storing the value.
+/// - `#[sync(message = "...")]` on the struct or enum renames the message enum,
+///   and `#[sync(derive(...))]` adds to its derives.
+///   `#[sync(vis = "...")]` sets its visibility, `pub` by default. Since the
+///   enum is the type's `Synchronized::Message`, it can't be less visible than
+///   the type itself, so `vis = "pub(crate)"` on a `pub` type is an error that
+///   says so.
+/// - `#[sync(range = lo..=hi)]` and `#[sync(validate = "path")]`, where
+///   `path` is a `fn(&T) -> bool`, restrict a field's values. `update()`
+///   clamps values to the range and ignores rejected ones, while
+///   `try_update()` refuses both with a `struct_sync::UpdateError`. Control
+///   values are clamped to the range too.
+/// - `#[sync(min = .., max = ..)]` on a numeric control point maps control
+///   values, 0.0-1.0, onto `min..=max` with a `struct_sync::ControlScale`
+///   instead of converting them with `From<F32ControlValue>`.
+///   `#[sync(curve = "linear" | "log" | "exp")]` shapes the mapping, and
+///   `#[sync(step = ..)]` rounds to multiples of the step above `min`.
+/// - `Synchronized::diff()` compares `#[sync]` fields with `==`, or, given
+///   `#[sync(tolerance = ...)]` on the field, with `struct_sync::ApproxEq`.
+///   `#[sync(tolerance = ...)]` on the struct sets the tolerance for its `f32`
+///   and `f64` fields.
+/// - The message enum implements `struct_sync::Wire`, a compact binary
+///   encoding, when its field types do. Each message is a varint tag followed
+///   by the value: control points are tagged with their control index, the
+///   other fields follow in field order, and a whole-value message, tagged
+///   last, is sent as its `to_messages()` and rebuilt on a default value.
+/// - The message enum's `Display` writes it as text, like `cutoff=0.5` or
+///   `oscillator/kind=square`, using the field types' own `Display` and
+///   quoting values that wouldn't read back the same, and
+///   `struct_sync::ParseLine::parse_line()` reads such a line back with their
+///   `FromStr`. A field whose type isn't `Display` is written as just its
+///   name, like `steps`, and `ParseLine` exists only when every field type is
+///   `FromStr`. A whole-value message is written as one line per field.
+/// - `#[sync(serde)]` on the struct or enum, with `struct_sync`'s `serde`
+///   feature, makes the message enum and `<Name>Kind` derive `Serialize` and
+///   `Deserialize`, tagged with the same kebab-case names as `IntoStaticStr`
+///   and `EnumString`, so the type and its field types need to implement them
+///   too. The feature alone derives nothing, so that turning it on can't break
+///   types whose fields aren't serializable; each type asks for it.
+/// - `#[sync(merge)]` marks a field whose type implements `struct_sync::Merge`,
+///   such as a `struct_sync::OrSet`, so that `struct_sync::Crdt` merges
+///   concurrent edits to it instead of keeping only the newer one. It isn't a
+///   control point.
+/// - `struct_sync::ControlValues` reads control points back as control
+///   values, inverting `#[sync(min = .., max = ..)]` scales and otherwise
+///   converting with `F32ControlValue`'s `From`. It exists when every control
+///   point's type has that conversion. `<Name>Kind` converts to the middle of
+///   its share of 0.0-1.0.
+/// - `Synchronized::PARAMS` describes the type's own control points with
+///   `struct_sync::ParamInfo`s, and `Synchronized::param_info()` looks one up
+///   by control index, nested fields' included, named with their full paths.
+///   Its type, range and step count come from the field's type, `range`,
+///   `min`, `max` and `step`; `#[sync(display_name = "...")]`,
+///   `#[sync(unit = "...")]`, `#[sync(default = ..)]` and `#[sync(hidden)]`
+///   fill in the rest. Ranges, scales and defaults must be constant
+///   expressions.
 #[proc_macro_derive(Synchronization, attributes(sync))]
 pub fn synchronization_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = input.ident;
-    let enum_name = format_ident!("{}Message", struct_name);
-    TokenStream::from(parse_synchronization_data(
-        &struct_name,
-        &input.generics,
-        &enum_name,
-        &input.data,
-    ))
+    TokenStream::from(
+        ContainerAttrs::parse(&input.attrs)
+            .and_then(|container| {
+                let enum_name = container
+                    .message
+                    .clone()
+                    .unwrap_or_else(|| format_ident!("{}Message", struct_name));
+                parse_synchronization_data(
+                    &struct_name,
+                    &input.vis,
+                    &input.generics,
+                    &enum_name,
+                    &input.data,
+                    &container,
+                )
+            })
+            .unwrap_or_else(Error::into_compile_error),
+    )
+}
+
+/// A `#[sync]` field, along with the names derived from it.
+struct SyncField {
+    /// The type its message variant carries: the field's type exactly as
+    /// written in the struct, or the child's message type for nested fields.
+    ty: Type,
+    /// The message variant that carries a new value, e.g., `AppleCount`.
+    variant: Ident,
+    /// The kebab-case name used for control points, e.g., `apple-count`.
+    name: String,
+    /// Whether the field can be set from an `F32ControlValue`.
+    controllable: bool,
+    /// Whether the field's type derives `Synchronization` itself and gets
+    /// its messages routed to it (`#[sync(nested)]`).
+    nested: bool,
+    /// The statement that `update` runs to store the new value `v`.
+    store: proc_macro2::TokenStream,
+    /// The field itself, the enum variant that holds it, if any, and its type
+    /// as written.
+    member: Member,
+    owner: Option<Ident>,
+    field_ty: Type,
+    /// Whether this is an enum's `Kind` pseudo-field rather than a real one.
+    kind: bool,
+    /// The field's own `#[sync(tolerance = ...)]`.
+    tolerance: Option<Expr>,
+    /// The field's `#[sync(range = lo..=hi)]` bounds and
+    /// `#[sync(validate = "...")]` function.
+    range: Option<(Expr, Expr)>,
+    validate: Option<syn::Path>,
+    /// The statement that `try_update` runs to store the new value `v`, which
+    /// differs from `store` only for nested fields.
+    try_store: proc_macro2::TokenStream,
+    /// The field's own `#[sync(on_change = "...")]` hook.
+    on_change: Option<LitStr>,
+    /// Whether CRDT merges combine the field's values (`#[sync(merge)]`).
+    merge: bool,
+    /// The `struct_sync::ControlScale` that `#[sync(min = .., max = ..)]`
+    /// asks for, as an expression.
+    scale: Option<proc_macro2::TokenStream>,
+    /// What `#[sync(display_name = "...", unit = "...", default = .., hidden)]`
+    /// add to the control point's `struct_sync::ParamInfo`.
+    display_name: Option<LitStr>,
+    unit: Option<LitStr>,
+    default: Option<Expr>,
+    hidden: bool,
+    /// Where to point diagnostics about this field.
+    span: Span,
+}
+
+impl SyncField {
+    /// `owner` is the enum variant that holds the field, or `None` for a field
+    /// of a struct.
+    fn new(
+        member: Member,
+        owner: Option<&Ident>,
+        ty: &Type,
+        attrs: &FieldAttrs,
+    ) -> syn::Result<Self> {
+        check_field_type(ty)?;
+        let name = attrs.name.as_ref();
+        let snake = match (name, &member) {
+            (Some(name), _) => snake_name(name)?,
+            (None, Member::Named(ident)) => ident.unraw().to_string(),
+            (None, Member::Unnamed(index)) => format!("field_{}", index.index),
+        };
+        let snake = match (name, owner) {
+            (None, Some(owner)) => format!(
+                "{}_{}",
+                owner.unraw().to_string().to_case(Case::Snake),
+                snake
+            ),
+            _ => snake,
+        };
+        let (message_ty, controllable) = if attrs.nested {
+            (nested_message_type(ty)?, false)
+        } else {
+            (
+                ty.clone(),
+                attrs.merge.is_none()
+                    && attrs
+                        .control
+                        .as_ref()
+                        .map_or_else(|| is_controllable_type(ty), |control| control.value),
+            )
+        };
+        let validate =
+            match &attrs.validate {
+                Some(path) => Some(path.parse().map_err(|_| {
+                    Error::new(path.span(), "validate takes the path to a function")
+                })?),
+                None => None,
+            };
+        let kebab = snake.from_case(Case::Snake).to_case(Case::Kebab);
+        let scale = match (&attrs.min, &attrs.max) {
+            (Some(min), Some(_)) if !controllable => {
+                return Err(Error::new_spanned(
+                    min,
+                    format!(
+                        "min and max scale control values, but `{}` isn't a control point",
+                        kebab
+                    ),
+                ))
+            }
+            (Some(min), Some(max)) => {
+                let curve = format_ident!(
+                    "{}",
+                    attrs
+                        .curve
+                        .as_ref()
+                        .map_or_else(|| "linear".to_string(), LitStr::value)
+                        .to_case(Case::Pascal)
+                );
+                let step = match &attrs.step {
+                    Some(step) => quote! { ::core::option::Option::Some((#step) as f64) },
+                    None => quote! { ::core::option::Option::None },
+                };
+                Some(quote! {
+                    ::struct_sync::ControlScale {
+                        min: (#min) as f64,
+                        max: (#max) as f64,
+                        curve: ::struct_sync::Curve::#curve,
+                        step: #step,
+                    }
+                })
+            }
+            _ => None,
+        };
+        match (attrs.param_span(), &attrs.default) {
+            (Some(span), _) if !controllable => {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "display_name, unit, default and hidden describe control points, but `{}` isn't one",
+                        kebab
+                    ),
+                ))
+            }
+            (_, Some(default)) if param_type(ty) == "Other" => {
+                return Err(Error::new_spanned(
+                    default,
+                    "default is in the field's own units, so it needs a number or bool field",
+                ))
+            }
+            _ => {}
+        }
+        let store = Self::store(&member, owner, attrs.nested);
+        let try_store = match owner {
+            _ if !attrs.nested => store.clone(),
+            Some(owner) => quote! {
+                if let Self::#owner { #member: field, .. } = self {
+                    ::struct_sync::Synchronized::try_update(field, v)
+                        .map_err(|e| e.nested_in(#kebab))?;
+                }
+            },
+            None => quote! {
+                ::struct_sync::Synchronized::try_update(&mut self.#member, v)
+                    .map_err(|e| e.nested_in(#kebab))?;
+            },
+        };
+        Ok(Self {
+            ty: message_ty,
+            variant: format_ident!("{}", snake.to_case(Case::Pascal)),
+            name: kebab,
+            controllable,
+            nested: attrs.nested,
+            store,
+            try_store,
+            range: attrs.range.clone(),
+            validate,
+            span: name.map_or_else(|| member.span(), LitStr::span),
+            member,
+            owner: owner.cloned(),
+            field_ty: ty.clone(),
+            kind: false,
+            tolerance: attrs.tolerance.clone(),
+            on_change: attrs.on_change.clone(),
+            merge: attrs.merge.is_some(),
+            scale,
+            display_name: attrs.display_name.clone(),
+            unit: attrs.unit.clone(),
+            default: attrs.default.clone(),
+            hidden: attrs.hidden.is_some(),
+        })
+    }
+
+    /// Whether `value`, a `&FieldType` expression, is within the field's
+    /// range, if it has one.
+    fn in_range(&self, value: proc_macro2::TokenStream) -> Option<proc_macro2::TokenStream> {
+        let ty = &self.field_ty;
+        self.range.as_ref().map(|(lo, hi)| {
+            quote! {{
+                let (lo, hi): (#ty, #ty) = (#lo, #hi);
+                lo <= *#value && *#value <= hi
+            }}
+        })
+    }
+
+    /// Rebinds the new value `v` to the nearest value in the field's range.
+    /// NaN and other values that don't compare clamp to the low end.
+    fn clamp(&self) -> proc_macro2::TokenStream {
+        let ty = &self.field_ty;
+        match &self.range {
+            Some((lo, hi)) => quote! {
+                let v = {
+                    let (lo, hi): (#ty, #ty) = (#lo, #hi);
+                    if !(v >= lo) {
+                        lo
+                    } else if v > hi {
+                        hi
+                    } else {
+                        v
+                    }
+                };
+            },
+            None => quote! {},
+        }
+    }
+
+    /// What the field's validator makes of `value`, a `&FieldType`
+    /// expression, if it has one.
+    fn accepts(&self, value: proc_macro2::TokenStream) -> Option<proc_macro2::TokenStream> {
+        self.validate.as_ref().map(|path| quote! { #path(#value) })
+    }
+
+    /// The body of `update`'s arm for this field: the new value `v` is
+    /// clamped to the field's range and dropped if the validator rejects it.
+    fn update_arm(&self) -> proc_macro2::TokenStream {
+        let (clamp, store) = (self.clamp(), &self.store);
+        match self.accepts(quote!(&v)) {
+            Some(accepts) => quote! {
+                #clamp
+                if #accepts {
+                    #store
+                }
+            },
+            None => quote! {
+                #clamp
+                #store
+            },
+        }
+    }
+
+    /// The body of `try_update`'s arm for this field, which refuses values
+    /// that are out of range or rejected.
+    fn try_update_arm(&self) -> proc_macro2::TokenStream {
+        let checks = self.checks(quote!((&v)));
+        let try_store = &self.try_store;
+        quote! {
+            #checks
+            #try_store
+        }
+    }
+
+    /// Returns the `UpdateError` for `value`, a `&FieldType` expression, if
+    /// it's out of range or rejected.
+    fn checks(&self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        let in_range = self.in_range(value.clone()).map(|in_range| {
+            quote! {
+                if !#in_range {
+                    return ::core::result::Result::Err(::struct_sync::UpdateError::OutOfRange {
+                        field: ::std::string::String::from(#name),
+                    });
+                }
+            }
+        });
+        let accepts = self.accepts(value).map(|accepts| {
+            quote! {
+                if !#accepts {
+                    return ::core::result::Result::Err(::struct_sync::UpdateError::Rejected {
+                        field: ::std::string::String::from(#name),
+                    });
+                }
+            }
+        });
+        quote! { #in_range #accepts }
+    }
+
+    /// Part of `validate()`: checks the field's current value, or hands the
+    /// check to a nested child.
+    fn validate(&self) -> proc_macro2::TokenStream {
+        let read = self.read(quote!(self));
+        let name = &self.name;
+        if self.nested {
+            quote! {
+                if let ::core::option::Option::Some(v) = #read {
+                    ::struct_sync::Synchronized::validate(v).map_err(|e| e.nested_in(#name))?;
+                }
+            }
+        } else if self.range.is_some() || self.validate.is_some() {
+            let checks = self.checks(quote!(v));
+            quote! {
+                if let ::core::option::Option::Some(v) = #read {
+                    #checks
+                }
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// Part of `clamp_to_ranges()`: clamps the field's current value, or has a
+    /// nested child clamp its own.
+    fn clamp_in_place(&self) -> proc_macro2::TokenStream {
+        let read = self.read_mut();
+        if self.nested {
+            quote! {
+                if let ::core::option::Option::Some(v) = #read {
+                    ::struct_sync::Synchronized::clamp_to_ranges(v);
+                }
+            }
+        } else if self.range.is_some() {
+            let clamp = self.clamp();
+            quote! {
+                if let ::core::option::Option::Some(field) = #read {
+                    let v = ::core::clone::Clone::clone(field);
+                    #clamp
+                    *field = v;
+                }
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// The field's value for a control point set to `value`, scaled to its
+    /// min and max and clamped to its range.
+    fn control_value(&self) -> proc_macro2::TokenStream {
+        let ty = &self.field_ty;
+        let v = match &self.scale {
+            Some(scale) => quote! {
+                <#ty as ::struct_sync::Scalar>::from_f64((#scale).denormalize(value))
+            },
+            None => quote! {
+                <#ty as ::struct_sync::__private::ControlPoint>::from_control_value(value)
+            },
+        };
+        match &self.range {
+            Some(_) => {
+                let clamp = self.clamp();
+                quote! {{
+                    let v: #ty = #v;
+                    #clamp
+                    v
+                }}
+            }
+            None => v,
+        }
+    }
+
+    /// An `Option<F32ControlValue>` expression for the control point's current
+    /// value in `self`: the inverse of `control_value()`.
+    fn current_control_value(&self) -> proc_macro2::TokenStream {
+        let read = self.read(quote!(self));
+        match &self.scale {
+            Some(scale) => quote! {
+                (#read).map(|v| (#scale).normalize(::struct_sync::Scalar::to_f64(*v)))
+            },
+            None => quote! {
+                (#read).map(|v| ::core::convert::From::from(::core::clone::Clone::clone(v)))
+            },
+        }
+    }
+
+    /// A `struct_sync::ParamInfo` expression that describes this control
+    /// point. It's evaluated as a constant.
+    fn param_info(&self) -> proc_macro2::TokenStream {
+        let (name, ty) = (&self.name, &self.field_ty);
+        let display_name = match &self.display_name {
+            Some(display_name) => display_name.value(),
+            None => name.from_case(Case::Kebab).to_case(Case::Title),
+        };
+        let value_type = param_type(ty);
+        let variant_names =
+            quote! { <#ty as ::struct_sync::__private::strum::VariantNames>::VARIANTS };
+        let value_type = match value_type {
+            _ if self.kind => quote! { ::struct_sync::ParamType::Choice(#variant_names) },
+            _ => {
+                let value_type = format_ident!("{}", value_type);
+                quote! { ::struct_sync::ParamType::#value_type }
+            }
+        };
+        let default = match &self.default {
+            Some(default) if param_type(ty) == "Bool" => {
+                quote! { ::core::option::Option::Some((#default) as u8 as f64) }
+            }
+            Some(default) => quote! { ::core::option::Option::Some((#default) as f64) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let range = match (&self.range, &self.scale) {
+            (Some((lo, hi)), _) if matches!(param_type(ty), "Int" | "Float") => quote! {
+                ::core::option::Option::Some(((#lo) as f64, (#hi) as f64))
+            },
+            (_, Some(scale)) => quote! {
+                ::core::option::Option::Some(((#scale).min, (#scale).max))
+            },
+            _ => quote! { ::core::option::Option::None },
+        };
+        let unit = match &self.unit {
+            Some(unit) => quote! { ::core::option::Option::Some(#unit) },
+            None => quote! { ::core::option::Option::None },
+        };
+        // Steps span the range the field really takes, and integers without
+        // a step still move one at a time.
+        let step_count = match &self.scale {
+            _ if self.kind => quote! { #variant_names.len() as u32 - 1 },
+            _ if param_type(ty) == "Bool" => quote! { 1 },
+            Some(scale) => {
+                let step = match param_type(ty) {
+                    "Int" => quote! {
+                        match scale.step {
+                            ::core::option::Option::Some(step) => ::core::option::Option::Some(step),
+                            ::core::option::Option::None => ::core::option::Option::Some(1.0),
+                        }
+                    },
+                    _ => quote! { scale.step },
+                };
+                quote! {{
+                    let scale = #scale;
+                    let (min, max) = match #range {
+                        ::core::option::Option::Some(range) => range,
+                        ::core::option::Option::None => (scale.min, scale.max),
+                    };
+                    ::struct_sync::ControlScale {
+                        min,
+                        max,
+                        step: #step,
+                        ..scale
+                    }
+                    .step_count()
+                }}
+            }
+            None if self.range.is_some() && param_type(ty) == "Int" => quote! {
+                match #range {
+                    ::core::option::Option::Some((min, max)) => ::struct_sync::ControlScale {
+                        min,
+                        max,
+                        curve: ::struct_sync::Curve::Linear,
+                        step: ::core::option::Option::Some(1.0),
+                    }
+                    .step_count(),
+                    ::core::option::Option::None => 0,
+                }
+            },
+            None => quote! { 0 },
+        };
+        let scale = match &self.scale {
+            Some(scale) => quote! { ::core::option::Option::Some(#scale) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let flags = [
+            (self.hidden, "HIDDEN"),
+            (self.validate.is_some(), "VALIDATED"),
+            (self.owner.is_some(), "VARIANT"),
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, flag)| format_ident!("{}", flag));
+        quote! {
+            ::struct_sync::ParamInfo {
+                name: #name,
+                display_name: #display_name,
+                value_type: #value_type,
+                default: #default,
+                range: #range,
+                unit: #unit,
+                step_count: #step_count,
+                scale: #scale,
+                flags: ::struct_sync::ParamFlags::NONE #( .union(::struct_sync::ParamFlags::#flags) )*,
+            }
+        }
+    }
+
+    /// The body of `update_recording`'s arm for this field: applies the
+    /// message `v` and evaluates to the message that undoes it. That's the
+    /// old value, or for a nested field, the child's inverse. Switching enum
+    /// variants, or a field of an inactive variant, takes a snapshot instead.
+    fn record_arm(&self, struct_name: &Ident, enum_name: &Ident) -> proc_macro2::TokenStream {
+        let variant = &self.variant;
+        let snapshot = quote! { #enum_name::#struct_name(::core::clone::Clone::clone(self)) };
+        if self.kind {
+            return quote! {
+                let inverse = #snapshot;
+                ::struct_sync::Synchronized::update(self, #enum_name::#variant(v));
+                inverse
+            };
+        }
+        if self.nested {
+            let read = self.read_mut();
+            return quote! {
+                match #read {
+                    ::core::option::Option::Some(child) => #enum_name::#variant(
+                        ::struct_sync::Synchronized::update_recording(child, v),
+                    ),
+                    ::core::option::Option::None => #snapshot,
+                }
+            };
+        }
+        let read = self.read(quote!(self));
+        quote! {
+            let inverse = match #read {
+                ::core::option::Option::Some(old) => {
+                    #enum_name::#variant(::core::clone::Clone::clone(old))
+                }
+                ::core::option::Option::None => #snapshot,
+            };
+            ::struct_sync::Synchronized::update(self, #enum_name::#variant(v));
+            inverse
+        }
+    }
+
+    /// `message_path`'s match arm for this field. A message that replaces a
+    /// nested child as a whole has the nested field's own path.
+    fn message_path_arm(&self, enum_name: &Ident) -> proc_macro2::TokenStream {
+        let (name, variant) = (&self.name, &self.variant);
+        if self.nested {
+            let ty = &self.field_ty;
+            quote! {
+                #enum_name::#variant(m) => ::core::option::Option::Some(
+                    match <#ty as ::struct_sync::Synchronized>::message_path(m) {
+                        ::core::option::Option::Some(path) => ::std::format!("{}/{}", #name, path),
+                        ::core::option::Option::None => ::std::string::String::from(#name),
+                    },
+                ),
+            }
+        } else {
+            quote! {
+                #enum_name::#variant(_) => ::core::option::Option::Some(
+                    ::std::string::String::from(#name),
+                ),
+            }
+        }
+    }
+
+    /// `is_variant_switch`'s match arm for this field, if it can switch a
+    /// variant: an enum's `kind`, or a nested field whose child can.
+    fn variant_switch_arm(&self, enum_name: &Ident) -> Option<proc_macro2::TokenStream> {
+        let variant = &self.variant;
+        if self.kind {
+            Some(quote! { #enum_name::#variant(_) => true, })
+        } else if self.nested {
+            let ty = &self.field_ty;
+            Some(quote! {
+                #enum_name::#variant(m) => <#ty as ::struct_sync::Synchronized>::is_variant_switch(m),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// `merge_messages`'s match arm for this field, if it has one: mergeable
+    /// fields merge with `struct_sync::Merge`, and nested fields hand their
+    /// messages to the child.
+    fn merge_arm(&self, enum_name: &Ident) -> Option<proc_macro2::TokenStream> {
+        let variant = &self.variant;
+        if self.nested {
+            let ty = &self.field_ty;
+            Some(quote! {
+                (#enum_name::#variant(mine), #enum_name::#variant(theirs)) => {
+                    <#ty as ::struct_sync::Synchronized>::merge_messages(mine, theirs)
+                }
+            })
+        } else if self.merge {
+            Some(quote! {
+                (#enum_name::#variant(mine), #enum_name::#variant(theirs)) => {
+                    ::struct_sync::Merge::merge(mine, theirs);
+                    true
+                }
+            })
+        } else {
+            None
+        }
+    }
+
+    /// `Display`'s match arm for this field, which writes `name=value`, or just
+    /// `name` if the field type isn't `Display`, or, for nested fields, the
+    /// child's lines prefixed with `name/`.
+    fn display_arm(&self, enum_name: &Ident) -> proc_macro2::TokenStream {
+        let (name, variant) = (&self.name, &self.variant);
+        if self.nested {
+            quote! {
+                #enum_name::#variant(m) => {
+                    let text = ::std::string::ToString::to_string(m);
+                    for (i, line) in text.lines().enumerate() {
+                        if i > 0 {
+                            f.write_str("\n")?;
+                        }
+                        ::core::write!(f, "{}/{}", #name, line)?;
+                    }
+                    ::core::result::Result::Ok(())
+                }
+            }
+        } else {
+            quote! {
+                #enum_name::#variant(v) => {
+                    #[allow(unused_imports)]
+                    use ::struct_sync::__private::{WriteFieldName as _, WriteFieldValue as _};
+                    (&::struct_sync::__private::FieldText(v)).write_field(#name, f)
+                }
+            }
+        }
+    }
+
+    /// `parse_field`'s match arm for this field. Nested fields match on their
+    /// own name and hand the rest of the path to the child.
+    fn parse_arm(&self, enum_name: &Ident) -> proc_macro2::TokenStream {
+        let (name, variant, ty) = (&self.name, &self.variant, &self.ty);
+        if self.nested {
+            quote! {
+                ::core::option::Option::Some((#name, rest)) => {
+                    <#ty as ::struct_sync::ParseLine>::parse_field(rest, value)
+                        .map(#enum_name::#variant)
+                        .map_err(|e| e.nested_in(#name))
+                }
+            }
+        } else {
+            quote! {
+                _ if path == #name => ::struct_sync::__private::read_value(value)
+                    .and_then(|value| value.parse::<#ty>().ok())
+                    .map(#enum_name::#variant)
+                    .ok_or_else(|| ::struct_sync::ParseLineError::InvalidValue {
+                        field: ::std::string::String::from(#name),
+                    }),
+            }
+        }
+    }
+
+    /// Like `read()`, but an `Option<&mut FieldType>` from `self`.
+    fn read_mut(&self) -> proc_macro2::TokenStream {
+        let member = &self.member;
+        match &self.owner {
+            Some(owner) => quote! {
+                if let Self::#owner { #member: field, .. } = self {
+                    ::core::option::Option::Some(field)
+                } else {
+                    ::core::option::Option::None
+                }
+            },
+            None => quote! { ::core::option::Option::Some(&mut self.#member) },
+        }
+    }
+
+    /// An `Option<&FieldType>` expression that reads the field from
+    /// `receiver`, which is `None` when the field belongs to an inactive enum
+    /// variant.
+    fn read(&self, receiver: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let member = &self.member;
+        match &self.owner {
+            _ if self.kind => quote! { ::core::option::Option::Some(&#receiver.kind()) },
+            Some(owner) => quote! {
+                if let Self::#owner { #member: field, .. } = #receiver {
+                    ::core::option::Option::Some(field)
+                } else {
+                    ::core::option::Option::None
+                }
+            },
+            None => quote! { ::core::option::Option::Some(&#receiver.#member) },
+        }
+    }
+
+    /// Appends the messages that set this field to its value in `self`, if
+    /// the field belongs to the active variant.
+    fn to_messages(&self, enum_name: &Ident) -> proc_macro2::TokenStream {
+        let variant = &self.variant;
+        let push = if self.nested {
+            quote! {
+                messages.extend(
+                    ::struct_sync::Synchronized::to_messages(v)
+                        .into_iter()
+                        .map(#enum_name::#variant),
+                );
+            }
+        } else {
+            quote! { messages.push(#enum_name::#variant(::core::clone::Clone::clone(v))); }
+        };
+        let read = self.read(quote!(self));
+        quote! {
+            if let ::core::option::Option::Some(v) = #read {
+                #push
+            }
+        }
+    }
+
+    /// Appends the messages that change this field from its value in `self`
+    /// to its value in `other`, if it has changed. A field of `other`'s
+    /// variant that `self` lacks is compared against its default, since
+    /// switching variants resets it. `default_tolerance` applies to `f32` and
+    /// `f64` fields without a tolerance of their own.
+    fn diff(
+        &self,
+        enum_name: &Ident,
+        default_tolerance: Option<&Expr>,
+    ) -> proc_macro2::TokenStream {
+        let variant = &self.variant;
+        if self.kind {
+            return quote! {
+                if self.kind() != other.kind() {
+                    messages.push(#enum_name::#variant(other.kind()));
+                }
+            };
+        }
+        let tolerance = self
+            .tolerance
+            .as_ref()
+            .or(default_tolerance.filter(|_| is_float_type(&self.field_ty)));
+        let (read_self, read_other) = (self.read(quote!(self)), self.read(quote!(other)));
+        let push = if self.nested {
+            quote! {
+                messages.extend(
+                    ::struct_sync::Synchronized::diff(a, b)
+                        .into_iter()
+                        .map(#enum_name::#variant),
+                );
+            }
+        } else {
+            let changed = match tolerance {
+                Some(tolerance) => {
+                    quote! { !::struct_sync::ApproxEq::approx_eq(a, b, #tolerance) }
+                }
+                None => quote! { a != b },
+            };
+            quote! {
+                if #changed {
+                    messages.push(#enum_name::#variant(::core::clone::Clone::clone(b)));
+                }
+            }
+        };
+        match &self.owner {
+            Some(_) => quote! {
+                if let ::core::option::Option::Some(b) = #read_other {
+                    let default;
+                    let a = match #read_self {
+                        ::core::option::Option::Some(a) => a,
+                        ::core::option::Option::None => {
+                            default = ::core::default::Default::default();
+                            &default
+                        }
+                    };
+                    #push
+                }
+            },
+            None => {
+                let member = &self.member;
+                quote! {
+                    {
+                        let (a, b) = (&self.#member, &other.#member);
+                        #push
+                    }
+                }
+            }
+        }
+    }
+
+    /// A getter, plus a `set_<field>()` setter that runs the `on_change` hook,
+    /// for a named struct field. Nested fields are changed by message, so they
+    /// get only the getter.
+    fn accessors(&self, on_change: Option<&LitStr>) -> syn::Result<proc_macro2::TokenStream> {
+        let ident = match &self.member {
+            Member::Named(ident) => ident,
+            Member::Unnamed(_) => unreachable!("accessors are only generated for named fields"),
+        };
+        let ty = &self.field_ty;
+        let getter = if self.nested || self.merge || !is_controllable_type(ty) {
+            quote! {
+                pub fn #ident(&self) -> &#ty {
+                    &self.#ident
+                }
+            }
+        } else {
+            quote! {
+                pub fn #ident(&self) -> #ty {
+                    ::core::clone::Clone::clone(&self.#ident)
+                }
+            }
+        };
+        if self.nested {
+            return Ok(getter);
+        }
+        let hook = match self.on_change.as_ref().or(on_change) {
+            Some(hook) => {
+                let path: syn::Path = hook.parse().map_err(|_| {
+                    Error::new(
+                        hook.span(),
+                        "on_change takes the name of a method or the path to a function",
+                    )
+                })?;
+                match path.get_ident() {
+                    Some(method) => quote! { self.#method(); },
+                    None => quote! { #path(self); },
+                }
+            }
+            None => quote! {},
+        };
+        let setter = format_ident!("set_{}", ident.unraw());
+        Ok(quote! {
+            #getter
+            pub fn #setter(&mut self, #ident: #ty) {
+                self.#ident = #ident;
+                #hook
+            }
+        })
+    }
+
+    /// Named struct fields go through the struct's `set_<field>` method so it
+    /// can react to the change; tuple structs are plain values, so they're
+    /// assigned. Enum fields are assigned only while their variant is active.
+    /// Nested fields hand the message to the child's `update`.
+    fn store(member: &Member, owner: Option<&Ident>, nested: bool) -> proc_macro2::TokenStream {
+        match (owner, member) {
+            (Some(owner), _) if nested => quote! {
+                if let Self::#owner { #member: field, .. } = self {
+                    ::struct_sync::Synchronized::update(field, v);
+                }
+            },
+            (Some(owner), _) => quote! {
+                if let Self::#owner { #member: field, .. } = self {
+                    *field = v;
+                }
+            },
+            (None, _) if nested => quote! {
+                ::struct_sync::Synchronized::update(&mut self.#member, v)
+            },
+            (None, Member::Named(ident)) => {
+                let setter = format_ident!("set_{}", ident.unraw());
+                quote! { self.#setter(v) }
+            }
+            (None, Member::Unnamed(_)) => quote! { self.#member = v },
+        }
+    }
+}
+
+/// The message type of a nested field's type, which only a named type that
+/// derives `Synchronization` has.
+fn nested_message_type(ty: &Type) -> syn::Result<Type> {
+    match ty {
+        Type::Group(t) => nested_message_type(&t.elem),
+        Type::Paren(t) => nested_message_type(&t.elem),
+        Type::Path(_) => Ok(parse_quote!(<#ty as ::struct_sync::Synchronized>::Message)),
+        _ => Err(Error::new_spanned(
+            ty,
+            "#[sync(nested)] fields must name a type that derives Synchronization",
+        )),
+    }
+}
+
+/// Accepts `#[sync(name = "...")]` in snake_case or kebab-case.
+fn snake_name(name: &LitStr) -> syn::Result<String> {
+    let value = name.value().replace('-', "_");
+    let mut chars = value.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(value.to_lowercase())
+    } else {
+        Err(Error::new(
+            name.span(),
+            "sync names start with a letter and contain only letters, digits, `_` and `-`",
+        ))
+    }
+}
+
+/// A message variant has to own a sized value of the field's type, which rules
+/// out borrowed, unsized and inferred types.
+fn check_field_type(ty: &Type) -> syn::Result<()> {
+    let problem = match ty {
+        Type::Group(t) => return check_field_type(&t.elem),
+        Type::Paren(t) => return check_field_type(&t.elem),
+        Type::Array(_) | Type::BareFn(_) | Type::Path(_) | Type::Tuple(_) => return Ok(()),
+        Type::Reference(_) => {
+            "borrowed fields can't be synchronized; messages must own their values"
+        }
+        Type::Ptr(_) => "raw pointer fields can't be synchronized",
+        Type::Slice(_) | Type::TraitObject(_) => {
+            "unsized fields can't be synchronized; try boxing the value"
+        }
+        Type::Macro(_) => {
+            "fields whose types come from macros can't be synchronized; try a type alias"
+        }
+        _ => "this field type can't be carried by a message",
+    };
+    Err(Error::new_spanned(ty, problem))
+}
+
+fn is_float_type(ty: &Type) -> bool {
+    match ty {
+        Type::Group(t) => is_float_type(&t.elem),
+        Type::Paren(t) => is_float_type(&t.elem),
+        Type::Path(t) => t.path.is_ident("f32") || t.path.is_ident("f64"),
+        _ => false,
+    }
+}
+
+/// The `struct_sync::ParamType` variant for a field type, judged by its name:
+/// `Bool`, `Int`, `Float` or `Other`.
+fn param_type(ty: &Type) -> &'static str {
+    const INTS: &[&str] = &[
+        "u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize",
+    ];
+    match ty {
+        Type::Group(t) => param_type(&t.elem),
+        Type::Paren(t) => param_type(&t.elem),
+        Type::Path(t) if t.path.is_ident("bool") => "Bool",
+        Type::Path(t) if INTS.iter().any(|int| t.path.is_ident(int)) => "Int",
+        _ if is_float_type(ty) => "Float",
+        _ => "Other",
+    }
+}
+
+/// Standard containers, arrays and tuples can't implement
+/// `From<F32ControlValue>`, so fields of those types are synchronized by
+/// message but aren't exposed as control points. Aliases and other types
+/// can't be told apart by name, so `#[sync(control = ...)]` overrides this.
+fn is_controllable_type(ty: &Type) -> bool {
+    const CONTAINERS: &[&str] = &[
+        "Option", "Vec", "VecDeque", "HashMap", "HashSet", "BTreeMap", "BTreeSet",
+    ];
+    match ty {
+        Type::Array(_) | Type::Tuple(_) | Type::Slice(_) => false,
+        Type::Group(t) => is_controllable_type(&t.elem),
+        Type::Paren(t) => is_controllable_type(&t.elem),
+        Type::Path(t) => match t.path.segments.last() {
+            Some(segment) => match segment.arguments {
+                PathArguments::AngleBracketed(_) => !CONTAINERS.iter().any(|c| segment.ident == c),
+                _ => segment.ident != "String",
+            },
+            None => true,
+        },
+        _ => true,
+    }
+}
+
+/// Collects the `#[sync]` fields and the `#[sync(outbound)]` field, if any,
+/// reporting every bad field at once rather than one per build.
+fn parse_sync_fields(
+    fields: &Fields,
+    owner: Option<&Ident>,
+) -> syn::Result<(Vec<SyncField>, Option<Member>)> {
+    let mut sync_fields: Vec<SyncField> = Vec::default();
+    let mut outbound = None;
+    let mut errors: Option<Error> = None;
+    for (i, f) in fields.iter().enumerate() {
+        let member = match &f.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        };
+        let attrs = match FieldAttrs::parse(&f.attrs) {
+            Ok(attrs) => attrs,
+            Err(e) => {
+                combine_error(&mut errors, e);
+                continue;
+            }
+        };
+        if let Some(span) = attrs.outbound {
+            if owner.is_some() {
+                combine_error(
+                    &mut errors,
+                    Error::new(span, "enums can't have a #[sync(outbound)] field"),
+                );
+            } else if outbound.is_some() {
+                combine_error(
+                    &mut errors,
+                    Error::new(span, "only one field can be #[sync(outbound)]"),
+                );
+            } else {
+                outbound = Some(member);
+            }
+        } else if attrs.sync {
+            match SyncField::new(member, owner, &f.ty, &attrs) {
+                Ok(field) => sync_fields.push(field),
+                Err(e) => combine_error(&mut errors, e),
+            }
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok((sync_fields, outbound)),
+    }
+}
+
+/// The message enum always derives some traits, and its name can't clash with
+/// the other types the derive works with.
+fn check_message_options(
+    struct_name: &Ident,
+    vis: &Visibility,
+    data: &Data,
+    enum_name: &Ident,
+    container: &ContainerAttrs,
+) -> syn::Result<()> {
+    const ALWAYS_DERIVED: &[&str] = &[
+        "Clone",
+        "Display",
+        "Debug",
+        "EnumCount",
+        "EnumCountMacro",
+        "EnumString",
+        "FromRepr",
+        "IntoStaticStr",
+    ];
+    let mut errors = None;
+    if enum_name == struct_name {
+        combine_error(
+            &mut errors,
+            Error::new(
+                enum_name.span(),
+                "the message enum needs a name of its own, not the name of the type it synchronizes",
+            ),
+        );
+    } else if matches!(data, Data::Enum(_)) && *enum_name == format!("{}Kind", struct_name) {
+        combine_error(
+            &mut errors,
+            Error::new(
+                enum_name.span(),
+                format!("`{}` is already the name of the enum's kind", enum_name),
+            ),
+        );
+    }
+    // The enum is the type's `Synchronized::Message`, which a public impl
+    // can't name if it's less visible than the type.
+    if let Some(message_vis) = &container.vis {
+        if visibility_rank(message_vis) < visibility_rank(vis) {
+            combine_error(
+                &mut errors,
+                Error::new(
+                    container.vis_span.unwrap_or_else(Span::call_site),
+                    format!(
+                        "the message enum is `{}`'s `Synchronized::Message`, so it can't be less visible than `{}` itself",
+                        struct_name, struct_name
+                    ),
+                ),
+            );
+        }
+    }
+    for derive in container.derives.iter().flatten() {
+        if let Some(name) = derive.segments.last().map(|s| &s.ident) {
+            if ALWAYS_DERIVED.iter().any(|d| name == d) {
+                combine_error(
+                    &mut errors,
+                    Error::new_spanned(derive, format!("message enums always derive `{}`", name)),
+                );
+            }
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+/// Orders visibilities from private (0) to `pub` (3). `pub(super)` and
+/// `pub(in path)` count as between private and `pub(crate)`.
+fn visibility_rank(vis: &Visibility) -> u8 {
+    match vis {
+        Visibility::Public(_) => 3,
+        Visibility::Crate(_) => 2,
+        Visibility::Restricted(r) if r.path.is_ident("crate") => 2,
+        Visibility::Restricted(r) if r.path.is_ident("self") => 0,
+        Visibility::Restricted(_) => 1,
+        Visibility::Inherited => 0,
+    }
+}
+
+/// The getters and setters that `#[sync(accessors)]` asks for, which only
+/// make sense on a struct with named fields.
+fn accessor_methods(
+    data: &Data,
+    container: &ContainerAttrs,
+    sync_fields: &[SyncField],
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut errors = None;
+    let accessors = match container.accessors {
+        Some(span) => match data {
+            Data::Struct(DataStruct {
+                fields: Fields::Named(_),
+                ..
+            }) => true,
+            _ => {
+                return Err(Error::new(
+                    span,
+                    "#[sync(accessors)] needs a struct with named fields",
+                ))
+            }
+        },
+        None => false,
+    };
+    let mut methods = Vec::default();
+    for f in sync_fields {
+        match (&f.on_change, accessors) {
+            (Some(hook), false) => combine_error(
+                &mut errors,
+                Error::new(
+                    hook.span(),
+                    "on_change hooks run from generated setters, so they need #[sync(accessors)] on the struct",
+                ),
+            ),
+            (Some(hook), true) if f.nested => combine_error(
+                &mut errors,
+                Error::new(
+                    hook.span(),
+                    "nested fields are changed by message and get no setter, so they take no on_change hook",
+                ),
+            ),
+            (_, true) => match f.accessors(container.on_change.as_ref()) {
+                Ok(tokens) => methods.push(tokens),
+                Err(e) => combine_error(&mut errors, e),
+            },
+            (None, false) => {}
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(quote! { #( #methods )* }),
+    }
+}
+
+fn combine_error(errors: &mut Option<Error>, e: Error) {
+    match errors.as_mut() {
+        Some(errors) => errors.combine(e),
+        None => *errors = Some(e),
+    }
+}
+
+/// Every message variant needs its own name, and none can shadow the
+/// whole-struct variant.
+fn check_names(struct_name: &Ident, data: &Data, sync_fields: &[SyncField]) -> syn::Result<()> {
+    let (whole, has_kind) = match data {
+        Data::Enum(_) => ("whole-enum", true),
+        _ => ("whole-struct", false),
+    };
+    let mut errors = None;
+    for (i, field) in sync_fields.iter().enumerate() {
+        if has_kind && i == 0 && field.variant == *struct_name {
+            return Err(Error::new(
+                field.span,
+                "an enum named `Kind` would need two message variants named `Kind`; rename the enum",
+            ));
+        } else if field.variant == *struct_name {
+            combine_error(
+                &mut errors,
+                Error::new(
+                    field.span,
+                    format!(
+                        "this field's message variant would be named `{}`, which is taken by the {} variant; rename it with #[sync(name = \"...\")]",
+                        struct_name, whole,
+                    ),
+                ),
+            );
+        } else if sync_fields[..i]
+            .iter()
+            .any(|other| other.variant == field.variant)
+        {
+            combine_error(
+                &mut errors,
+                Error::new(field.span, format!("duplicate sync name `{}`", field.name)),
+            );
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+/// Finds the nested field that owns control point `index`, which counts past
+/// this struct's own `own_count` control points, and evaluates the matching
+/// `bodies` entry with `child` and `index` rebased onto that child.
+fn walk_nested(
+    own_count: usize,
+    reads: &[proc_macro2::TokenStream],
+    bodies: impl Iterator<Item = proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    if reads.is_empty() {
+        return quote! {
+            let _ = index;
+            ::core::option::Option::None
+        };
+    }
+    quote! {
+        let mut index = index - #own_count;
+        #(
+            if let ::core::option::Option::Some(child) = #reads {
+                let count = ::struct_sync::__private::groove_core::traits::Controllable::control_index_count(child);
+                if index < count {
+                    return #bodies;
+                }
+                index -= count;
+            }
+        )*
+        ::core::option::Option::None
+    }
+}
+
+/// With `#[sync(serde)]`, the generated enums derive `Serialize` and
+/// `Deserialize`, tagged with the same kebab-case names as strum uses. Returns
+/// the derives, with a trailing comma, and the enum's `#[serde]` attribute.
+fn serde_derives(
+    container: &ContainerAttrs,
+) -> (
+    Option<proc_macro2::TokenStream>,
+    Option<proc_macro2::TokenStream>,
+) {
+    if container.serde.is_none() {
+        return (None, None);
+    }
+    (
+        Some(quote! {
+            ::struct_sync::__private::serde::Serialize,
+            ::struct_sync::__private::serde::Deserialize,
+        }),
+        Some(quote! {
+            #[serde(crate = "::struct_sync::__private::serde", rename_all = "kebab-case")]
+        }),
+    )
+}
+
+/// For enums, the message that switches the active variant carries a
+/// generated fieldless `<Enum>Kind`. Switching to a different variant resets
+/// all of its fields to their defaults.
+fn parse_enum_kind(
+    enum_name: &Ident,
+    vis: &Visibility,
+    data: &DataEnum,
+    container: &ContainerAttrs,
+) -> syn::Result<(SyncField, proc_macro2::TokenStream)> {
+    if data.variants.is_empty() {
+        return Err(Error::new(
+            data.enum_token.span,
+            "Synchronization needs an enum with at least one variant",
+        ));
+    }
+    let kind_name = format_ident!("{}Kind", enum_name);
+    let variants: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+    let constructors = data.variants.iter().map(|v| {
+        let ident = &v.ident;
+        match &v.fields {
+            Fields::Named(fields) => {
+                let names = fields.named.iter().map(|f| &f.ident);
+                quote! { Self::#ident { #( #names: ::core::default::Default::default() ),* } }
+            }
+            Fields::Unnamed(fields) => {
+                let defaults = fields
+                    .unnamed
+                    .iter()
+                    .map(|_| quote! { ::core::default::Default::default() });
+                quote! { Self::#ident ( #( #defaults ),* ) }
+            }
+            Fields::Unit => quote! { Self::#ident },
+        }
+    });
+    let count = variants.len();
+    let indexes = 0..count;
+    let last = &variants[count - 1];
+    let leading = &variants[..count - 1];
+    let wire_indexes = (0..count as u64).map(Literal::u64_unsuffixed);
+    let (serde_derives, serde_attrs) = serde_derives(container);
+    let kind_block = quote! {
+        #[derive(
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::fmt::Debug,
+            ::core::default::Default,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::hash::Hash,
+            #serde_derives
+            ::struct_sync::__private::strum_macros::Display,
+            ::struct_sync::__private::strum_macros::EnumString,
+            ::struct_sync::__private::strum_macros::EnumVariantNames,
+        )]
+        #[strum(crate = "::struct_sync::__private::strum", serialize_all = "kebab-case")]
+        #serde_attrs
+        #vis enum #kind_name {
+            #[default]
+            #( #variants ),*
+        }
+        impl ::core::convert::From<::struct_sync::__private::groove_core::control::F32ControlValue> for #kind_name {
+            fn from(value: ::struct_sync::__private::groove_core::control::F32ControlValue) -> Self {
+                match (value.0 * #count as f32) as usize {
+                    #( #indexes => Self::#leading, )*
+                    _ => Self::#last,
+                }
+            }
+        }
+        // The middle of the variant's share of 0.0-1.0, so that converting
+        // back is immune to rounding.
+        impl ::core::convert::From<#kind_name> for ::struct_sync::__private::groove_core::control::F32ControlValue {
+            fn from(kind: #kind_name) -> Self {
+                Self((kind as usize as f32 + 0.5) / #count as f32)
+            }
+        }
+        impl ::struct_sync::Wire for #kind_name {
+            fn encode(&self, out: &mut ::std::vec::Vec<u8>) {
+                ::struct_sync::Wire::encode(&(*self as u64), out)
+            }
+
+            fn decode_from(input: &mut &[u8]) -> ::core::result::Result<Self, ::struct_sync::DecodeError> {
+                match <u64 as ::struct_sync::Wire>::decode_from(input)? {
+                    #( #wire_indexes => ::core::result::Result::Ok(Self::#variants), )*
+                    _ => ::core::result::Result::Err(::struct_sync::DecodeError::Malformed),
+                }
+            }
+        }
+    };
+    let store = quote! {
+        if self.kind() != v {
+            *self = match v {
+                #( #kind_name::#variants => #constructors ),*
+            };
+        }
+    };
+    let kind = SyncField {
+        ty: parse_quote!(#kind_name),
+        variant: format_ident!("Kind"),
+        name: "kind".to_string(),
+        controllable: true,
+        nested: false,
+        try_store: store.clone(),
+        store,
+        member: Member::Named(format_ident!("kind")),
+        owner: None,
+        field_ty: parse_quote!(#kind_name),
+        kind: true,
+        tolerance: None,
+        range: None,
+        validate: None,
+        on_change: None,
+        merge: false,
+        scale: None,
+        display_name: None,
+        unit: None,
+        default: None,
+        hidden: false,
+        span: enum_name.span(),
+    };
+    Ok((kind, kind_block))
 }
 
 fn parse_synchronization_data(
     struct_name: &Ident,
+    vis: &Visibility,
     generics: &Generics,
     enum_name: &Ident,
     data: &Data,
-) -> proc_macro2::TokenStream {
-    let (_impl_generics, ty_generics, _where_clause) = generics.split_for_impl();
-    let mut enum_set_method_names = Vec::default();
-    let mut enum_set_method_original_names = Vec::default();
-    let mut enum_snake_names = Vec::default();
-    let mut enum_variant_names = Vec::default();
-    let mut enum_variant_fields = Vec::default();
+    container: &ContainerAttrs,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     // Code adapted from https://blog.turbo.fish/proc-macro-error-handling/
     // Thank you!
-    let fields = match data {
+    let mut kind_block = quote! {};
+    let mut kind_impl = quote! {};
+    let mut outbound = None;
+    let sync_fields = match data {
         Data::Struct(DataStruct {
-            fields: Fields::Named(fields),
+            fields: Fields::Unit,
+            struct_token,
             ..
-        }) => &fields.named,
-        _ => panic!("this derive macro only works on structs with named fields"),
-    };
-    let sync_fields = fields.into_iter().fold(Vec::default(), |mut v, f| {
-        let attrs: Vec<_> = f
-            .attrs
-            .iter()
-            .filter(|attr| attr.path.is_ident("sync"))
-            .collect();
-        if !attrs.is_empty() {
-            match &f.ty {
-                syn::Type::Path(t) => {
-                    if let Some(ident) = t.path.get_ident() {
-                        v.push((f.ident.as_ref().unwrap().clone(), ident.clone()));
+        }) => {
+            return Err(Error::new(
+                struct_token.span,
+                "Synchronization needs a struct with fields, but this is a unit struct",
+            ))
+        }
+        Data::Struct(DataStruct { fields, .. }) => {
+            let (sync_fields, member) = parse_sync_fields(fields, None)?;
+            outbound = member;
+            sync_fields
+        }
+        Data::Enum(data) => {
+            let (kind, block) = parse_enum_kind(struct_name, vis, data, container)?;
+            let kind_name = &kind.ty;
+            let variants = data.variants.iter().map(|v| &v.ident);
+            kind_impl = quote! {
+                /// Which variant is active.
+                pub fn kind(&self) -> #kind_name {
+                    match self {
+                        #( Self::#variants { .. } => #kind_name::#variants ),*
                     }
                 }
-                _ => todo!(),
+            };
+            kind_block = block;
+            let mut sync_fields = vec![kind];
+            let mut errors = None;
+            for variant in &data.variants {
+                match parse_sync_fields(&variant.fields, Some(&variant.ident)) {
+                    Ok((fields, _)) => sync_fields.extend(fields),
+                    Err(e) => combine_error(&mut errors, e),
+                }
             }
+            if let Some(errors) = errors {
+                return Err(errors);
+            }
+            sync_fields
         }
-        v
-    });
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                "Synchronization can only be derived for structs and enums",
+            ))
+        }
+    };
+    check_names(struct_name, data, &sync_fields)?;
+    check_message_options(struct_name, vis, data, enum_name, container)?;
+    let message_vis = match &container.vis {
+        Some(vis) => quote! { #vis },
+        None => quote! { pub },
+    };
+    let accessor_methods = accessor_methods(data, container, &sync_fields)?;
 
-    for (field_name, field_type) in sync_fields {
-        enum_set_method_names.push(format_ident!(
-            "set_and_propagate_{}",
-            field_name.to_string(),
-        ));
-        enum_set_method_original_names.push(format_ident!("set_{}", field_name.to_string(),));
-        enum_snake_names.push(format_ident!("{}", field_name.to_string(),));
-        enum_variant_names.push(format_ident!(
-            "{}",
-            field_name.to_string().to_case(Case::Pascal),
-        ));
-        enum_variant_fields.push(format_ident!("{}", field_type));
-    }
+    let update_arms = sync_fields.iter().map(SyncField::update_arm);
+    let try_update_arms = sync_fields.iter().map(SyncField::try_update_arm);
+    let field_validates = sync_fields.iter().map(SyncField::validate);
+    let record_arms = sync_fields
+        .iter()
+        .map(|f| f.record_arm(struct_name, enum_name));
+    let message_path_arms = sync_fields.iter().map(|f| f.message_path_arm(enum_name));
+    let merge_arms = sync_fields.iter().filter_map(|f| f.merge_arm(enum_name));
+    let variant_switch_arms = sync_fields
+        .iter()
+        .filter_map(|f| f.variant_switch_arm(enum_name));
+    let field_clamps = sync_fields.iter().map(SyncField::clamp_in_place);
+    let control_values: Vec<_> = sync_fields
+        .iter()
+        .filter(|f| f.controllable)
+        .map(SyncField::control_value)
+        .collect();
+    let field_to_messages = sync_fields.iter().map(|f| f.to_messages(enum_name));
+    let field_diffs = sync_fields
+        .iter()
+        .map(|f| f.diff(enum_name, container.tolerance.as_ref()));
+    let enum_variant_names: Vec<_> = sync_fields.iter().map(|f| &f.variant).collect();
+    let enum_variant_fields: Vec<_> = sync_fields.iter().map(|f| &f.ty).collect();
+    let enum_variant_serialize_names: Vec<_> = sync_fields.iter().map(|f| &f.name).collect();
+
+    let controllable_fields: Vec<_> = sync_fields.iter().filter(|f| f.controllable).collect();
+    let control_indexes: Vec<_> = (0..controllable_fields.len()).collect();
+    let control_variant_names: Vec<_> = controllable_fields.iter().map(|f| &f.variant).collect();
+    let control_names: Vec<_> = controllable_fields.iter().map(|f| &f.name).collect();
+    let control_index_count = controllable_fields.len();
+    let uncontrollable_variant_names: Vec<_> = sync_fields
+        .iter()
+        .filter(|f| !f.controllable && !f.nested)
+        .map(|f| &f.variant)
+        .collect();
+
+    // Nested children's control points follow this struct's own, in field
+    // order, and their names are prefixed with the field's name, like
+    // `filter/cutoff`.
+    let nested_fields: Vec<_> = sync_fields.iter().filter(|f| f.nested).collect();
+    let nested_variant_names: Vec<_> = nested_fields.iter().map(|f| &f.variant).collect();
+    let nested_names: Vec<_> = nested_fields.iter().map(|f| &f.name).collect();
+    let nested_reads: Vec<_> = nested_fields.iter().map(|f| f.read(quote!(self))).collect();
+    let nested_message_for_index = walk_nested(
+        control_index_count,
+        &nested_reads,
+        nested_fields.iter().map(|f| {
+            let variant = &f.variant;
+            quote! { ::struct_sync::Synchronized::message_for_index(child, index, value).map(#enum_name::#variant) }
+        }),
+    );
+    let nested_control_path_for_index = walk_nested(
+        control_index_count,
+        &nested_reads,
+        nested_fields.iter().map(|f| {
+            let name = &f.name;
+            quote! { ::struct_sync::Synchronized::control_path_for_index(child, index).map(|path| ::std::format!("{}/{}", #name, path)) }
+        }),
+    );
+
+    // The sink belongs to this replica, so replacing the whole value keeps it.
+    let replace_self = match &outbound {
+        Some(outbound) => quote! {
+            let outbound = ::core::mem::take(&mut self.#outbound);
+            *self = v;
+            self.#outbound = outbound;
+        },
+        None => quote! { *self = v; },
+    };
+    let propagate_methods = match &outbound {
+        Some(outbound) => {
+            let methods = sync_fields.iter().filter(|f| !f.nested).map(|f| {
+                let method = format_ident!("set_and_propagate_{}", f.name.replace('-', "_"));
+                let (ty, variant, store) = (&f.ty, &f.variant, &f.store);
+                let clamp = f.clamp();
+                let guard = f.accepts(quote!(&v)).map(|accepts| {
+                    quote! {
+                        if !#accepts {
+                            return;
+                        }
+                    }
+                });
+                quote! {
+                    /// Sets the field, then sends the message that makes the same change
+                    /// to the `#[sync(outbound)]` sink. Like `update()`, clamps the value
+                    /// to the field's range and ignores values its validator rejects.
+                    pub fn #method(&mut self, v: #ty) {
+                        #clamp
+                        #guard
+                        let message = #enum_name::#variant(::core::clone::Clone::clone(&v));
+                        #store;
+                        self.#outbound.send(message);
+                    }
+                }
+            });
+            quote! { #( #methods )* }
+        }
+        None => quote! {},
+    };
 
+    // EnumString and FromRepr build variants out of Default::default(), and
+    // FromRepr refuses lifetimes, so generic structs go without them.
+    let parsing_derives = if generics.params.is_empty() {
+        quote! {
+            ::struct_sync::__private::strum_macros::EnumString,
+            ::struct_sync::__private::strum_macros::FromRepr,
+        }
+    } else {
+        quote! {}
+    };
+    let extra_derives = container.derives.iter().flatten();
+    let (serde_derives, serde_attrs) = serde_derives(container);
+    let serde_renames = enum_variant_serialize_names.iter().map(|name| {
+        serde_attrs
+            .is_some()
+            .then(|| quote! { #[serde(rename = #name)] })
+    });
     let enum_block = quote! {
-        #[derive(Clone, Display, Debug, EnumCountMacro, EnumString, FromRepr, IntoStaticStr)]
-        #[strum(serialize_all = "kebab-case")]
-        pub enum #enum_name {
-            #struct_name ( #struct_name ),
-            #( #enum_variant_names ( #enum_variant_fields ) ),*
+        #[derive(
+            ::core::clone::Clone,
+            ::core::fmt::Debug,
+            ::struct_sync::__private::strum_macros::EnumCount,
+            #parsing_derives
+            #serde_derives
+            ::struct_sync::__private::strum_macros::IntoStaticStr
+            #(, #extra_derives)*
+        )]
+        #[strum(crate = "::struct_sync::__private::strum", serialize_all = "kebab-case")]
+        #serde_attrs
+        #message_vis enum #enum_name #generics #where_clause {
+            #struct_name ( #struct_name #ty_generics ),
+            #(
+                #[strum(serialize = #enum_variant_serialize_names)]
+                #serde_renames
+                #enum_variant_names ( #enum_variant_fields )
+            ),*
         }
     };
-    let impl_block = quote! {
-        impl #generics #struct_name #ty_generics {
-            pub fn update(&mut self, message: #enum_name) {
+    // A parent's EnumString and FromRepr need a default for nested messages.
+    let mut default_where_clause = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    default_where_clause
+        .predicates
+        .push(parse_quote!(#struct_name #ty_generics: ::core::default::Default));
+    let default_block = quote! {
+        impl #impl_generics ::core::default::Default for #enum_name #ty_generics #default_where_clause {
+            fn default() -> Self {
+                Self::#struct_name(::core::default::Default::default())
+            }
+        }
+    };
+    let type_name = struct_name.unraw().to_string().to_case(Case::Kebab);
+    let param_infos = controllable_fields.iter().map(|f| f.param_info());
+    let nested_param_info = walk_nested(
+        control_index_count,
+        &nested_reads,
+        nested_fields
+            .iter()
+            .map(|f| {
+                let name = &f.name;
+                quote! {
+                    ::struct_sync::Synchronized::param_info(child, index).map(|info| ::struct_sync::ParamInfo {
+                        name: ::struct_sync::__private::intern(::std::format!("{}/{}", #name, info.name)),
+                        ..info
+                    })
+                }
+            }),
+    );
+    let synchronized_block = quote! {
+        impl #impl_generics ::struct_sync::Synchronized for #struct_name #ty_generics #where_clause {
+            type Message = #enum_name #ty_generics;
+
+            const NAME: &'static str = #type_name;
+
+            const PARAMS: &'static [::struct_sync::ParamInfo] = &[ #( #param_infos ),* ];
+
+            fn update(&mut self, message: #enum_name #ty_generics) {
+                match message {
+                    #enum_name::#struct_name(mut v) => {
+                        ::struct_sync::Synchronized::clamp_to_ranges(&mut v);
+                        if ::struct_sync::Synchronized::validate(&v).is_ok() {
+                            #replace_self
+                        }
+                    }
+                    #( #enum_name::#enum_variant_names(v) => { #update_arms } ),*
+                }
+            }
+
+            fn try_update(
+                &mut self,
+                message: #enum_name #ty_generics,
+            ) -> ::core::result::Result<(), ::struct_sync::UpdateError> {
                 match message {
-                    #enum_name::#struct_name(v) => *self = v,
-                    #( #enum_name::#enum_variant_names(v) => self.#enum_set_method_original_names(v) ),*
+                    #enum_name::#struct_name(v) => {
+                        ::struct_sync::Synchronized::validate(&v)?;
+                        #replace_self
+                    }
+                    #( #enum_name::#enum_variant_names(v) => { #try_update_arms } ),*
                 }
+                ::core::result::Result::Ok(())
             }
 
-            pub fn message_for_name(
+            fn update_recording(
+                &mut self,
+                message: #enum_name #ty_generics,
+            ) -> #enum_name #ty_generics {
+                match message {
+                    #enum_name::#struct_name(v) => {
+                        let inverse = #enum_name::#struct_name(::core::clone::Clone::clone(self));
+                        ::struct_sync::Synchronized::update(self, #enum_name::#struct_name(v));
+                        inverse
+                    }
+                    #( #enum_name::#enum_variant_names(v) => { #record_arms } ),*
+                }
+            }
+
+            fn message_path(message: &#enum_name #ty_generics) -> ::core::option::Option<::std::string::String> {
+                match message {
+                    #enum_name::#struct_name(_) => ::core::option::Option::None,
+                    #( #message_path_arms )*
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn is_variant_switch(message: &#enum_name #ty_generics) -> bool {
+                match message {
+                    #( #variant_switch_arms )*
+                    _ => false,
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn merge_messages(mine: &mut #enum_name #ty_generics, theirs: &#enum_name #ty_generics) -> bool {
+                match (mine, theirs) {
+                    #( #merge_arms )*
+                    _ => false,
+                }
+            }
+
+            fn validate(&self) -> ::core::result::Result<(), ::struct_sync::UpdateError> {
+                #( #field_validates )*
+                ::core::result::Result::Ok(())
+            }
+
+            fn clamp_to_ranges(&mut self) {
+                #( #field_clamps )*
+            }
+
+            fn message_for_name(
                 &self,
                 param_name: &str,
-                value: groove_core::control::F32ControlValue,
-            ) -> Option<#enum_name> {
-                if let Ok(message) = #enum_name::from_str(param_name) {
-                    self.parameterized_message_from_message(message, value)
-                } else {
-                    None
+                value: ::struct_sync::__private::groove_core::control::F32ControlValue,
+            ) -> ::core::option::Option<#enum_name #ty_generics> {
+                if let ::core::option::Option::Some((head, rest)) = param_name.split_once('/') {
+                    return match head {
+                        #( #nested_names => (#nested_reads)
+                            .and_then(|child| ::struct_sync::Synchronized::message_for_name(child, rest, value))
+                            .map(#enum_name::#nested_variant_names), )*
+                        _ => ::core::option::Option::None,
+                    };
+                }
+                match param_name {
+                    #( #control_names => ::core::option::Option::Some(#enum_name::#control_variant_names(#control_values)), )*
+                    _ => ::core::option::Option::None,
                 }
             }
 
-            pub fn message_for_index(
+            fn message_for_index(
                 &self,
                 param_index: usize,
-                value: groove_core::control::F32ControlValue,
-            ) -> Option<#enum_name> {
-                if let Some(message) = #enum_name::from_repr(param_index + 1) {
-                    self.parameterized_message_from_message(message, value)
-                } else {
-                    None
+                value: ::struct_sync::__private::groove_core::control::F32ControlValue,
+            ) -> ::core::option::Option<#enum_name #ty_generics> {
+                match param_index {
+                    #( #control_indexes => ::core::option::Option::Some(#enum_name::#control_variant_names(#control_values)), )*
+                    index => { #nested_message_for_index }
                 }
             }
 
-            pub fn parameterized_message_from_message(
+            fn parameterized_message_from_message(
                 &self,
-                message: #enum_name,
-                value: groove_core::control::F32ControlValue,
-            ) -> Option<#enum_name> {
+                message: #enum_name #ty_generics,
+                value: ::struct_sync::__private::groove_core::control::F32ControlValue,
+            ) -> ::core::option::Option<#enum_name #ty_generics> {
                 match message {
-                    #enum_name::#struct_name(_) => {return None;}
-                    #( #enum_name::#enum_variant_names(_) => {return Some(#enum_name::#enum_variant_names(value.into()));} )*
+                    #enum_name::#struct_name(_) => ::core::option::Option::None,
+                    #( #enum_name::#control_variant_names(_) => ::core::option::Option::Some(#enum_name::#control_variant_names(#control_values)), )*
+                    #( #enum_name::#uncontrollable_variant_names(_) => ::core::option::Option::None, )*
+                    #( #enum_name::#nested_variant_names(message) => (#nested_reads)
+                        .and_then(|child| ::struct_sync::Synchronized::parameterized_message_from_message(child, message, value))
+                        .map(#enum_name::#nested_variant_names), )*
                 }
             }
 
+            fn control_path_for_index(&self, index: usize) -> ::core::option::Option<::std::string::String> {
+                match index {
+                    #( #control_indexes => ::core::option::Option::Some(::std::string::String::from(#control_names)), )*
+                    index => { #nested_control_path_for_index }
+                }
+            }
+
+            fn param_info(&self, index: usize) -> ::core::option::Option<::struct_sync::ParamInfo> {
+                if let ::core::option::Option::Some(info) = <Self as ::struct_sync::Synchronized>::PARAMS.get(index) {
+                    return ::core::option::Option::Some(*info);
+                }
+                #nested_param_info
+            }
+
+            fn snapshot_message(&self) -> #enum_name #ty_generics {
+                #enum_name::#struct_name(::core::clone::Clone::clone(self))
+            }
+
+            fn to_messages(&self) -> ::std::vec::Vec<#enum_name #ty_generics> {
+                let mut messages = ::std::vec::Vec::new();
+                #( #field_to_messages )*
+                messages
+            }
+
+            fn diff(&self, other: &Self) -> ::std::vec::Vec<#enum_name #ty_generics> {
+                let mut messages = ::std::vec::Vec::new();
+                #( #field_diffs )*
+                messages
+            }
+        }
+    };
+    let impl_block = quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #kind_impl
+            #accessor_methods
+            #propagate_methods
+        }
+    };
+    // Control points are tagged with their control index, the other fields
+    // follow in field order, and the whole value comes last.
+    let mut next_tag = control_index_count as u64;
+    let mut control_tags = 0u64..;
+    let wire_tags: Vec<_> = sync_fields
+        .iter()
+        .map(|f| match f.controllable {
+            true => control_tags.next().unwrap(),
+            false => {
+                next_tag += 1;
+                next_tag - 1
+            }
+        })
+        .map(Literal::u64_unsuffixed)
+        .collect();
+    let whole_tag = Literal::u64_unsuffixed(next_tag);
+    // The bounds are higher-ranked so that they're only checked where the
+    // impl is used: a message whose fields can't be encoded simply doesn't
+    // implement `Wire`.
+    let mut wire_where_clause = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    for ty in &enum_variant_fields {
+        wire_where_clause
+            .predicates
+            .push(parse_quote!(for<'wire> #ty: ::struct_sync::Wire));
+    }
+    wire_where_clause
+        .predicates
+        .push(parse_quote!(for<'wire> #struct_name #ty_generics: ::core::default::Default));
+    let wire_block = quote! {
+        impl #impl_generics ::struct_sync::Wire for #enum_name #ty_generics #wire_where_clause {
+            fn encode(&self, out: &mut ::std::vec::Vec<u8>) {
+                match self {
+                    #enum_name::#struct_name(v) => {
+                        ::struct_sync::Wire::encode(&(#whole_tag as u64), out);
+                        ::struct_sync::Wire::encode(&::struct_sync::Synchronized::to_messages(v), out);
+                    }
+                    #( #enum_name::#enum_variant_names(v) => {
+                        ::struct_sync::Wire::encode(&(#wire_tags as u64), out);
+                        ::struct_sync::Wire::encode(v, out);
+                    } )*
+                }
+            }
+
+            fn decode_from(input: &mut &[u8]) -> ::core::result::Result<Self, ::struct_sync::DecodeError> {
+                match <u64 as ::struct_sync::Wire>::decode_from(input)? {
+                    #whole_tag => {
+                        let mut v: #struct_name #ty_generics = ::core::default::Default::default();
+                        for _ in 0..<usize as ::struct_sync::Wire>::decode_from(input)? {
+                            // A whole value goes out as its fields' messages,
+                            // never as another whole value, which would let
+                            // crafted input recurse without bound.
+                            let mut peek = *input;
+                            if <u64 as ::struct_sync::Wire>::decode_from(&mut peek)? == #whole_tag {
+                                return ::core::result::Result::Err(::struct_sync::DecodeError::Malformed);
+                            }
+                            let message = <Self as ::struct_sync::Wire>::decode_from(input)?;
+                            ::struct_sync::Synchronized::update(&mut v, message);
+                        }
+                        ::core::result::Result::Ok(#enum_name::#struct_name(v))
+                    }
+                    #( #wire_tags => ::struct_sync::Wire::decode_from(input).map(#enum_name::#enum_variant_names), )*
+                    tag => ::core::result::Result::Err(::struct_sync::DecodeError::UnknownTag(tag)),
+                }
+            }
+        }
+    };
+    // Like `Wire`, `ParseLine` exists only where the field types support it.
+    // `Display` always exists, but needs nested messages to be `Display` too.
+    let mut display_where_clause = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    let mut parse_where_clause = display_where_clause.clone();
+    for f in &sync_fields {
+        let ty = &f.ty;
+        if f.nested {
+            display_where_clause
+                .predicates
+                .push(parse_quote!(for<'t> #ty: ::core::fmt::Display));
+        }
+        parse_where_clause.predicates.push(match f.nested {
+            true => parse_quote!(for<'t> #ty: ::struct_sync::ParseLine),
+            false => parse_quote!(for<'t> #ty: ::core::str::FromStr),
+        });
+    }
+    let display_arms = sync_fields.iter().map(|f| f.display_arm(enum_name));
+    let parse_arms = sync_fields.iter().map(|f| f.parse_arm(enum_name));
+    let text_block = quote! {
+        impl #impl_generics ::core::fmt::Display for #enum_name #ty_generics #display_where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #enum_name::#struct_name(v) => {
+                        for (i, message) in ::struct_sync::Synchronized::to_messages(v).iter().enumerate() {
+                            if i > 0 {
+                                f.write_str("\n")?;
+                            }
+                            ::core::fmt::Display::fmt(message, f)?;
+                        }
+                        ::core::result::Result::Ok(())
+                    }
+                    #( #display_arms )*
+                }
+            }
+        }
+        impl #impl_generics ::struct_sync::ParseLine for #enum_name #ty_generics #parse_where_clause {
+            fn parse_field(
+                path: &str,
+                value: &str,
+            ) -> ::core::result::Result<Self, ::struct_sync::ParseLineError> {
+                match path.split_once('/') {
+                    #( #parse_arms )*
+                    _ => ::core::result::Result::Err(::struct_sync::ParseLineError::UnknownField {
+                        field: ::std::string::String::from(path),
+                    }),
+                }
+            }
         }
     };
     let controllable_block = quote! {
-        impl groove_core::traits::Controllable for #generics #struct_name #ty_generics {
-            fn control_name_for_index(&self, index: usize) -> Option<&'static str> {
-                if let Some(message) = #enum_name::from_repr(index + 1) {
-                    Some(message.into())
-                } else {
-                    None
+        impl #impl_generics ::struct_sync::__private::groove_core::traits::Controllable for #struct_name #ty_generics #where_clause {
+            fn control_name_for_index(&self, index: usize) -> ::core::option::Option<&'static str> {
+                match index {
+                    #( #control_indexes => ::core::option::Option::Some(#control_names), )*
+                    index => ::struct_sync::Synchronized::control_path_for_index(self, index)
+                        .map(::struct_sync::__private::intern),
                 }
             }
             fn control_index_count(&self) -> usize {
-                #enum_name::COUNT - 1
+                #control_index_count
+                #( + (#nested_reads).map_or(0, ::struct_sync::__private::groove_core::traits::Controllable::control_index_count) )*
             }
         }
     };
-    quote! {
+    // Like `Wire`, this exists only where every control point can be read.
+    let mut values_where_clause = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    for f in &sync_fields {
+        let ty = &f.field_ty;
+        if f.nested {
+            values_where_clause
+                .predicates
+                .push(parse_quote!(for<'c> #ty: ::struct_sync::ControlValues));
+        } else if f.controllable && f.scale.is_none() {
+            values_where_clause.predicates.push(parse_quote!(
+                for<'c> ::struct_sync::__private::groove_core::control::F32ControlValue:
+                    ::core::convert::From<#ty>
+            ));
+        }
+    }
+    let current_control_values: Vec<_> = controllable_fields
+        .iter()
+        .map(|f| f.current_control_value())
+        .collect();
+    let nested_control_value_for_index = walk_nested(
+        control_index_count,
+        &nested_reads,
+        nested_fields.iter().map(
+            |_| quote! { ::struct_sync::ControlValues::control_value_for_index(child, index) },
+        ),
+    );
+    let control_values_block = quote! {
+        impl #impl_generics ::struct_sync::ControlValues for #struct_name #ty_generics #values_where_clause {
+            fn control_value_for_index(
+                &self,
+                index: usize,
+            ) -> ::core::option::Option<::struct_sync::__private::groove_core::control::F32ControlValue> {
+                match index {
+                    #( #control_indexes => #current_control_values, )*
+                    index => { #nested_control_value_for_index }
+                }
+            }
+
+            fn control_value_for_name(
+                &self,
+                name: &str,
+            ) -> ::core::option::Option<::struct_sync::__private::groove_core::control::F32ControlValue> {
+                if let ::core::option::Option::Some((head, rest)) = name.split_once('/') {
+                    return match head {
+                        #( #nested_names => (#nested_reads)
+                            .and_then(|child| ::struct_sync::ControlValues::control_value_for_name(child, rest)), )*
+                        _ => ::core::option::Option::None,
+                    };
+                }
+                match name {
+                    #( #control_names => #current_control_values, )*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    };
+    Ok(quote! {
         #[automatically_derived]
         #enum_block
         #[automatically_derived]
+        #default_block
+        #[automatically_derived]
+        #synchronized_block
+        #[automatically_derived]
         #impl_block
         #[automatically_derived]
         #controllable_block
-    }
+        #[automatically_derived]
+        #control_values_block
+        #[automatically_derived]
+        #wire_block
+        #[automatically_derived]
+        #text_block
+        #kind_block
+    })
 }
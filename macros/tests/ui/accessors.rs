@@ -0,0 +1,43 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+#[sync(on_change = "recalculate")]
+struct NoAccessors {
+    #[sync]
+    volume: f32,
+}
+
+#[derive(Synchronization)]
+struct FieldHook {
+    #[sync(on_change = "recalculate")]
+    volume: f32,
+}
+
+#[derive(Synchronization)]
+#[sync(accessors)]
+struct Tuple(#[sync] f32);
+
+#[derive(Synchronization)]
+#[sync(accessors)]
+enum Source {
+    Line {
+        #[sync]
+        gain: f32,
+    },
+}
+
+#[derive(Synchronization)]
+#[sync(accessors, accessors)]
+struct Twice {
+    #[sync]
+    volume: f32,
+}
+
+#[derive(Synchronization)]
+#[sync(accessors)]
+struct BadHook {
+    #[sync(on_change = "not a path")]
+    volume: f32,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct StereoGain(#[sync(name = "left")] f32, #[sync(name = "left")] f32);
+
+#[derive(Synchronization)]
+struct Pan(#[sync(name = "2nd position")] f32);
+
+#[derive(Synchronization)]
+struct Tone(#[sync(name)] f32);
+
+#[derive(Synchronization)]
+struct Gain {
+    #[sync]
+    gain: f32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+#[sync]
+struct Stuff {
+    #[sync]
+    apple_count: usize,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Sequence {
+    #[sync(control = "no")]
+    steps: Steps,
+    #[sync(nested, control = false)]
+    clock: Clock,
+    #[sync(merge, control = true)]
+    tags: Tags,
+}
+
+fn main() {}
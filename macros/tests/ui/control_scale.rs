@@ -0,0 +1,21 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Orchard {
+    #[sync(min = 0)]
+    apple_count: usize,
+    #[sync(curve = "log")]
+    frequency: f32,
+    #[sync(min = 0.0, max = 1.0, curve = "square")]
+    quality: f32,
+    #[sync(min = 0, max = 10)]
+    labels: Vec<String>,
+    #[sync(min = 0.0, max = 20000.0, curve = "log")]
+    cutoff: f32,
+    #[sync(min = -1, max = 1, curve = "log")]
+    gain: f32,
+    #[sync(nested, min = 0.0, max = 1.0)]
+    tree: Tree,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+enum Never {}
+
+fn main() {}
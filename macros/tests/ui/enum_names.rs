@@ -0,0 +1,24 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+enum Kind {
+    Sine,
+}
+
+#[derive(Synchronization)]
+enum Waveform {
+    Square {
+        #[sync(name = "kind")]
+        duty: f32,
+    },
+    Pulse {
+        #[sync]
+        width: f32,
+    },
+    Saw {
+        #[sync(name = "pulse-width")]
+        slope: f32,
+    },
+}
+
+fn main() {}
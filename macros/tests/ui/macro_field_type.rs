@@ -0,0 +1,15 @@
+use struct_sync_macros::Synchronization;
+
+macro_rules! sample {
+    () => {
+        f32
+    };
+}
+
+#[derive(Synchronization)]
+struct Stuff {
+    #[sync]
+    sample: sample!(),
+}
+
+fn main() {}
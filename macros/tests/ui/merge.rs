@@ -0,0 +1,11 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Session {
+    #[sync(nested, merge)]
+    synth: Synth,
+    #[sync(merge, merge)]
+    tags: Tags,
+}
+
+fn main() {}
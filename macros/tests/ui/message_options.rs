@@ -0,0 +1,44 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+#[sync(message = "Stuff")]
+struct Stuff {
+    #[sync]
+    apple_count: usize,
+}
+
+#[derive(Synchronization)]
+#[sync(message = "WaveformKind")]
+enum Waveform {
+    Sine,
+}
+
+#[derive(Synchronization)]
+#[sync(derive(PartialEq, Clone, strum_macros::Display))]
+struct Things {
+    #[sync]
+    pear_count: usize,
+}
+
+#[derive(Synchronization)]
+#[sync(message = "not a name")]
+struct BadName {
+    #[sync]
+    count: usize,
+}
+
+#[derive(Synchronization)]
+#[sync(vis = "public")]
+struct BadVis {
+    #[sync]
+    count: usize,
+}
+
+#[derive(Synchronization)]
+#[sync(vis = "pub(crate)")]
+pub struct Knob {
+    #[sync]
+    level: f32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Synth {
+    #[sync(nested)]
+    filters: [Filter; 2],
+    #[sync(nested, nested)]
+    envelope: Envelope,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+#[sync(accessors)]
+struct Synth {
+    #[sync(nested, on_change = "recalculate")]
+    filter: Filter,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Mixer {
+    #[sync]
+    volume: f32,
+    #[sync(outbound)]
+    first: (),
+    #[sync(outbound)]
+    second: (),
+    #[sync(outbound, nested)]
+    third: (),
+}
+
+#[derive(Synchronization)]
+enum Source {
+    Line {
+        #[sync(outbound)]
+        outbound: (),
+    },
+}
+
+fn main() {}
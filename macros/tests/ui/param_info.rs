@@ -0,0 +1,13 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Orchard {
+    #[sync(unit = "apples")]
+    labels: Vec<String>,
+    #[sync(default = 1)]
+    variety: Variety,
+    #[sync(nested, display_name = "Tree")]
+    tree: Tree,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+#[sync(serde)]
+struct Synth {
+    #[sync]
+    level: f32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Orchard {
+    #[sync(step = 0.25)]
+    quality: f32,
+}
+
+fn main() {}
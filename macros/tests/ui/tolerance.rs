@@ -0,0 +1,13 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Synth {
+    #[sync(nested, tolerance = 0.01)]
+    filter: Filter,
+    #[sync(outbound, tolerance = 0.01)]
+    outbound: (),
+    #[sync(tolerance = 0.01, tolerance = 0.1)]
+    level: f32,
+}
+
+fn main() {}
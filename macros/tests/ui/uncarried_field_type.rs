@@ -0,0 +1,9 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Stuff {
+    #[sync]
+    never: !,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+union Sample {
+    f: f32,
+    i: i32,
+}
+
+fn main() {}
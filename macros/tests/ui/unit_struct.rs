@@ -0,0 +1,6 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Silence;
+
+fn main() {}
@@ -0,0 +1,11 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Samples {
+    #[sync]
+    gain: f32,
+    #[sync]
+    samples: [f32],
+}
+
+fn main() {}
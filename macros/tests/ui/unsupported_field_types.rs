@@ -0,0 +1,13 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Stuff<'a> {
+    #[sync]
+    name: &'a str,
+    #[sync]
+    apple_count: usize,
+    #[sync]
+    samples: *const f32,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use struct_sync_macros::Synchronization;
+
+#[derive(Synchronization)]
+struct Channel {
+    #[sync(range = 0.0..1.0)]
+    gain: f32,
+    #[sync(range = 0.0..)]
+    pan: f32,
+    #[sync(validate = "not a path")]
+    note: u8,
+    #[sync(nested, range = 0.0..=1.0)]
+    band: Band,
+    #[sync(nested, validate = "is_band")]
+    other_band: Band,
+}
+
+fn main() {}
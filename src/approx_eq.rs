@@ -0,0 +1,41 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+/// Equality within a tolerance, which `diff()` uses for fields marked
+/// `#[sync(tolerance = ...)]`. Implement it for a field's type to give it a
+/// tolerance.
+pub trait ApproxEq {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool;
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        (*self as f64).approx_eq(&(*other as f64), tolerance)
+    }
+}
+impl ApproxEq for f64 {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        // The equality check covers infinities, whose difference is NaN.
+        self == other || (self - other).abs() <= tolerance
+    }
+}
+impl<T: ApproxEq, const N: usize> ApproxEq for [T; N] {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        self.iter()
+            .zip(other)
+            .all(|(a, b)| a.approx_eq(b, tolerance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApproxEq;
+
+    #[test]
+    fn compares_within_tolerance() {
+        assert!(0.5f32.approx_eq(&0.5001, 0.001));
+        assert!(!0.5f32.approx_eq(&0.51, 0.001));
+        assert!(f64::INFINITY.approx_eq(&f64::INFINITY, 0.0));
+        assert!(!f64::NAN.approx_eq(&f64::NAN, 1.0));
+        assert!([1.0f32, 2.0].approx_eq(&[1.0, 2.0001], 0.001));
+    }
+}
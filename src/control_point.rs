@@ -0,0 +1,19 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use groove_core::control::F32ControlValue;
+
+/// What the derived code converts control values with, so that a field type
+/// that can't be a control point gets an error that says how to opt out.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be set from an `F32ControlValue`, so it can't be a control point",
+    label = "has no `From<F32ControlValue>`",
+    note = "mark the field `#[sync(control = false)]` to synchronize it by message only"
+)]
+pub trait ControlPoint: Sized {
+    fn from_control_value(value: F32ControlValue) -> Self;
+}
+impl<T: From<F32ControlValue>> ControlPoint for T {
+    fn from_control_value(value: F32ControlValue) -> Self {
+        T::from(value)
+    }
+}
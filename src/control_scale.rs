@@ -0,0 +1,160 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use groove_core::control::F32ControlValue;
+
+/// How a control value, 0.0-1.0, spreads over a field's `min..=max`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Curve {
+    /// Equal distances for equal turns of the knob.
+    #[default]
+    Linear,
+    /// Equal ratios for equal turns, as for frequencies: halfway between 20
+    /// and 20,000 is 632. `min` and `max` must both be positive; if they
+    /// aren't, the scale is linear instead.
+    Log,
+    /// Slow at the bottom and fast at the top, following `10^x`.
+    Exp,
+}
+
+/// Maps control values, 0.0-1.0, onto a field's real units and back, as
+/// given by `#[sync(min = .., max = .., curve = "..", step = ..)]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControlScale {
+    pub min: f64,
+    pub max: f64,
+    pub curve: Curve,
+    /// Rounds values to `min` plus a multiple of `step`.
+    pub step: Option<f64>,
+}
+impl ControlScale {
+    /// The real value for a control value, which is clamped to 0.0-1.0.
+    pub fn denormalize(&self, value: F32ControlValue) -> f64 {
+        let t = (value.0 as f64).clamp(0.0, 1.0);
+        let (min, max) = (self.min, self.max);
+        let v = match self.effective_curve() {
+            Curve::Linear => min + t * (max - min),
+            Curve::Log => min * (max / min).powf(t),
+            Curve::Exp => min + (max - min) * (10f64.powf(t) - 1.0) / 9.0,
+        };
+        let v = match self.step {
+            Some(step) if step > 0.0 => min + ((v - min) / step).round() * step,
+            _ => v,
+        };
+        v.clamp(min.min(max), min.max(max))
+    }
+
+    /// The control value for a real value, which is clamped to `min..=max`.
+    pub fn normalize(&self, value: f64) -> F32ControlValue {
+        let (min, max) = (self.min, self.max);
+        if min == max {
+            return F32ControlValue(0.0);
+        }
+        let v = value.clamp(min.min(max), min.max(max));
+        let t = match self.effective_curve() {
+            Curve::Linear => (v - min) / (max - min),
+            Curve::Log => (v / min).ln() / (max / min).ln(),
+            Curve::Exp => (1.0 + 9.0 * (v - min) / (max - min)).log10(),
+        };
+        F32ControlValue(t.clamp(0.0, 1.0) as f32)
+    }
+
+    /// The curve to follow, which is linear for a log curve over a range that
+    /// doesn't stay above zero.
+    fn effective_curve(&self) -> Curve {
+        match self.curve {
+            Curve::Log if self.min <= 0.0 || self.max <= 0.0 => Curve::Linear,
+            curve => curve,
+        }
+    }
+
+    /// How many steps a stepped scale takes from `min` to `max`, or 0 for a
+    /// continuous one.
+    pub const fn step_count(&self) -> u32 {
+        match self.step {
+            Some(step) if step > 0.0 => (((self.max - self.min) / step).abs() + 0.5) as u32,
+            _ => 0,
+        }
+    }
+}
+
+/// A numeric field type that a `ControlScale` can set. Integers round to the
+/// nearest value, saturating at their bounds.
+pub trait Scalar: Copy {
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+macro_rules! impl_scalar {
+    ($($ty:ty),*) => {
+        $(
+            impl Scalar for $ty {
+                fn from_f64(value: f64) -> Self {
+                    value.round() as $ty
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+impl_scalar!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+impl Scalar for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+impl Scalar for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_map_both_ways() {
+        let scale = |curve, step| ControlScale {
+            min: 20.0,
+            max: 20000.0,
+            curve,
+            step,
+        };
+        for curve in [Curve::Linear, Curve::Log, Curve::Exp] {
+            let scale = scale(curve, None);
+            assert_eq!(scale.denormalize(F32ControlValue(0.0)), 20.0);
+            assert!((scale.denormalize(F32ControlValue(1.0)) - 20000.0).abs() < 1e-6);
+            let v = scale.denormalize(F32ControlValue(0.3));
+            assert!((scale.normalize(v).0 - 0.3).abs() < 1e-6);
+        }
+        let log = scale(Curve::Log, None);
+        assert!((log.denormalize(F32ControlValue(0.5)) - 632.455).abs() < 0.001);
+        let stepped = scale(Curve::Linear, Some(100.0));
+        assert_eq!(stepped.denormalize(F32ControlValue(0.5)), 10020.0);
+        assert_eq!(stepped.denormalize(F32ControlValue(2.0)), 20000.0);
+        assert_eq!(stepped.step_count(), 200);
+        assert_eq!(log.step_count(), 0);
+    }
+
+    #[test]
+    fn log_curves_through_zero_are_linear() {
+        let scale = ControlScale {
+            min: 0.0,
+            max: 100.0,
+            curve: Curve::Log,
+            step: None,
+        };
+        assert_eq!(scale.denormalize(F32ControlValue(0.25)), 25.0);
+        assert_eq!(scale.normalize(50.0).0, 0.5);
+    }
+}
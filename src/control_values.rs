@@ -0,0 +1,18 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use groove_core::{control::F32ControlValue, traits::Controllable};
+
+/// Reads control points back as control values, for recording automation or
+/// drawing knobs. `#[derive(Synchronization)]` implements it when every
+/// control point can be read: fields scaled with `#[sync(min = .., max = ..)]`
+/// are normalized back onto 0.0-1.0, and the others need
+/// `impl From<FieldType> for F32ControlValue`.
+pub trait ControlValues: Controllable {
+    /// The control value of control point `index`, or `None` if there's no
+    /// such control point or it belongs to an inactive enum variant.
+    fn control_value_for_index(&self, index: usize) -> Option<F32ControlValue>;
+
+    /// Like `control_value_for_index()`, but names the control point like
+    /// `Synchronized::message_for_name()` does, e.g., `filter/cutoff`.
+    fn control_value_for_name(&self, name: &str) -> Option<F32ControlValue>;
+}
@@ -0,0 +1,140 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::{
+    replica::{edit_scope, written_fields},
+    Stamp, Stamped, Synchronized,
+};
+use std::collections::{btree_map::Entry, BTreeMap};
+
+/// A state-based CRDT. Merging is commutative, associative and idempotent, so
+/// replicas that have merged each other's states, in whatever order and
+/// however often, end up equal.
+pub trait Merge {
+    fn merge(&mut self, other: &Self);
+}
+
+/// Serializes a map as a sequence of key-value pairs, for formats such as
+/// JSON that only take strings as map keys.
+#[cfg(feature = "serde")]
+pub(crate) mod map_as_pairs {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    pub(crate) fn serialize<K, V, S>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map)
+    }
+
+    pub(crate) fn deserialize<'de, K, V, D>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+/// A `Synchronized` value that replicas edit independently, e.g., while
+/// offline, and then merge.
+///
+/// Each `#[sync]` field is a last-writer-wins register holding the newest
+/// message that set it, except that `#[sync(merge)]` fields, such as an
+/// `OrSet` or an `Rga`, combine concurrent edits with `Merge`. The value is
+/// rebuilt from the replicas' shared starting value by replaying the
+/// registers in stamp order.
+#[derive(Clone, Debug)]
+pub struct Crdt<T: Synchronized> {
+    base: T,
+    value: T,
+    replica: u32,
+    clock: u64,
+    registers: BTreeMap<String, Stamped<T::Message>>,
+}
+impl<T: Synchronized + Clone> Crdt<T>
+where
+    T::Message: Clone,
+{
+    /// `replica` must be unique among the replicas that merge with each
+    /// other, and they must all start from the same `value`.
+    pub fn new(replica: u32, value: T) -> Self {
+        Self {
+            base: value.clone(),
+            value,
+            replica,
+            clock: 0,
+            registers: BTreeMap::default(),
+        }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn replica(&self) -> u32 {
+        self.replica
+    }
+
+    /// Applies a local edit.
+    pub fn edit(&mut self, message: T::Message) {
+        self.clock += 1;
+        let stamp = Stamp {
+            time: self.clock,
+            replica: self.replica,
+        };
+        let scope = edit_scope::<T>(&message);
+        self.value.update(message);
+        for message in written_fields(&self.value, scope.as_deref()) {
+            if let Some(path) = T::message_path(&message) {
+                self.registers.insert(path, Stamped { stamp, message });
+            }
+        }
+        self.rebuild();
+    }
+
+    /// Replays the registers over the starting value. Within an edit, variant
+    /// switches go first, outer enums before inner ones, since they reset the
+    /// fields that the rest of the edit sets.
+    fn rebuild(&mut self) {
+        let mut registers: Vec<_> = self.registers.iter().collect();
+        registers.sort_by_key(|(path, stamped)| {
+            let switches_variant = T::is_variant_switch(&stamped.message);
+            (stamped.stamp, !switches_variant, path.matches('/').count())
+        });
+        let mut value = self.base.clone();
+        for (_, stamped) in registers {
+            value.update(stamped.message.clone());
+        }
+        self.value = value;
+    }
+}
+impl<T: Synchronized + Clone> Merge for Crdt<T>
+where
+    T::Message: Clone,
+{
+    fn merge(&mut self, other: &Self) {
+        self.clock = self.clock.max(other.clock);
+        for (path, theirs) in &other.registers {
+            match self.registers.entry(path.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(theirs.clone());
+                }
+                Entry::Occupied(mut entry) => {
+                    let mine = entry.get_mut();
+                    if T::merge_messages(&mut mine.message, &theirs.message) {
+                        mine.stamp = mine.stamp.max(theirs.stamp);
+                    } else if theirs.stamp > mine.stamp {
+                        *mine = theirs.clone();
+                    }
+                }
+            }
+        }
+        self.rebuild();
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use std::fmt::Display;
+
+/// Why `Wire::decode()` couldn't read a value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of a value.
+    Truncated,
+    /// A message's tag doesn't match any of its variants.
+    UnknownTag(u64),
+    /// The bytes don't encode a value of the expected type, e.g., a `bool`
+    /// other than 0 or 1, or a string that isn't UTF-8.
+    Malformed,
+    /// The input goes on after the value.
+    TrailingBytes,
+}
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "input ended in the middle of a value"),
+            Self::UnknownTag(tag) => write!(f, "unknown message tag {}", tag),
+            Self::Malformed => write!(f, "malformed value"),
+            Self::TrailingBytes => write!(f, "input continues after the value"),
+        }
+    }
+}
+impl std::error::Error for DecodeError {}
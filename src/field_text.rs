@@ -0,0 +1,112 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use std::{borrow::Cow, fmt};
+
+/// Writes `value` as it goes after the `=` of a message's text line: as is,
+/// or, when it wouldn't read back the same, in double quotes with `\\`, `\"`,
+/// `\n`, `\r` and `\t` escaped. That's a value with a line break, with
+/// whitespace at either end, or that starts with a quote.
+pub fn write_value(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    let plain = !value.starts_with('"') && value.trim() == value && !value.contains(['\n', '\r']);
+    if plain {
+        return f.write_str(value);
+    }
+    f.write_str("\"")?;
+    for c in value.chars() {
+        match c {
+            '\\' => f.write_str("\\\\")?,
+            '"' => f.write_str("\\\"")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c => fmt::Write::write_char(f, c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// Reads a value that `write_value()` wrote, or returns `None` if its quotes
+/// or escapes are malformed.
+pub fn read_value(value: &str) -> Option<Cow<'_, str>> {
+    if !value.starts_with('"') {
+        return Some(Cow::Borrowed(value));
+    }
+    let inner = value.get(1..)?.strip_suffix('"')?;
+    let mut unescaped = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '"' => return None,
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                '"' => '"',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(Cow::Owned(unescaped))
+}
+
+/// A message's field value on its way to `Display`. The derived `Display`
+/// calls `write_field()` on `&FieldText(value)`, which writes `name=value`
+/// through `WriteFieldValue` if the value is `Display`, and otherwise falls
+/// back, by autoref, to `WriteFieldName`, which writes just the name.
+pub struct FieldText<'a, T: ?Sized>(pub &'a T);
+
+pub trait WriteFieldValue {
+    fn write_field(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+impl<T: fmt::Display + ?Sized> WriteFieldValue for FieldText<'_, T> {
+    fn write_field(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{name}=")?;
+        write_value(f, &self.0.to_string())
+    }
+}
+
+pub trait WriteFieldName {
+    fn write_field(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+impl<T: ?Sized> WriteFieldName for &FieldText<'_, T> {
+    fn write_field(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Written<'a>(&'a str);
+    impl fmt::Display for Written<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write_value(f, self.0)
+        }
+    }
+
+    #[test]
+    fn values_read_back_as_written() {
+        assert_eq!(Written("0.5").to_string(), "0.5");
+        assert_eq!(Written("a=b").to_string(), "a=b");
+        assert_eq!(Written(" x\n\"").to_string(), r#"" x\n\"""#);
+        for value in [
+            "",
+            "0.5",
+            "a=b",
+            "line1\nlevel=0.9",
+            "  two  words ",
+            "\"",
+            "\\n\t",
+        ] {
+            let written = Written(value).to_string();
+            assert!(!written.contains('\n'));
+            assert_eq!(read_value(&written).as_deref(), Some(value));
+        }
+        assert_eq!(read_value("\"open"), None);
+        assert_eq!(read_value(r#""a"b""#), None);
+        assert_eq!(read_value(r#""\q""#), None);
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use std::{
+    collections::HashSet,
+    sync::{Mutex, OnceLock},
+};
+
+/// Returns a `'static` copy of `s`, leaking it the first time it's seen. The
+/// derived `control_name_for_index()` uses it for nested fields' control
+/// paths, like `filter/cutoff`, which are few and fixed, so the leak is
+/// bounded.
+pub fn intern(s: String) -> &'static str {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut interned = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    match interned.get(s.as_str()) {
+        Some(s) => s,
+        None => {
+            let s: &'static str = Box::leak(s.into_boxed_str());
+            interned.insert(s);
+            s
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::intern;
+
+    #[test]
+    fn interns_each_string_once() {
+        let a = intern("filter/cutoff".to_string());
+        let b = intern(String::from("filter/cutoff"));
+        assert_eq!(a, "filter/cutoff");
+        assert!(std::ptr::eq(a, b));
+    }
+}
@@ -1,4 +1,65 @@
+pub use approx_eq::ApproxEq;
+pub use control_scale::{ControlScale, Curve, Scalar};
+pub use control_values::ControlValues;
+pub use crdt::{Crdt, Merge};
+pub use decode_error::DecodeError;
+pub use midi::MidiMap;
+pub use or_set::OrSet;
+pub use outbound::Outbound;
+pub use param_info::{ParamFlags, ParamInfo, ParamType};
+pub use parse_line::ParseLine;
+pub use parse_line_error::ParseLineError;
+pub use replica::{ReplicaState, Stamp, Stamped};
+pub use rga::Rga;
+pub use struct_sync_macros::Synchronization;
+pub use synchronized::Synchronized;
+pub use undo::UndoStack;
+pub use update_error::UpdateError;
+pub use wire::Wire;
+
+// Lets the derive's `::struct_sync::...` paths resolve inside this crate too.
+extern crate self as struct_sync;
+
+/// What the derived code refers to, so that deriving `Synchronization` needs
+/// no `use` lines for strum or groove items. Not public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::control_point::ControlPoint;
+    pub use crate::field_text::{
+        read_value, write_value, FieldText, WriteFieldName, WriteFieldValue,
+    };
+    pub use crate::intern::intern;
+    pub use groove_core;
+    #[cfg(feature = "serde")]
+    pub use serde;
+    pub use strum;
+    pub use strum_macros;
+}
+
+mod approx_eq;
+mod control_point;
+mod control_scale;
+mod control_values;
+mod crdt;
+mod decode_error;
+mod field_text;
+mod intern;
+pub mod midi;
 #[allow(dead_code)]
 #[allow(unused_variables)]
 mod models;
 //mod models_EXPANDED;
+mod or_set;
+pub mod osc;
+mod outbound;
+mod param_info;
+mod parse_line;
+mod parse_line_error;
+mod replica;
+mod rga;
+mod synchronized;
+#[cfg(test)]
+mod tests;
+mod undo;
+mod update_error;
+mod wire;
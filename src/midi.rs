@@ -0,0 +1,152 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::Synchronized;
+use groove_core::control::F32ControlValue;
+use std::collections::HashMap;
+
+/// Binds MIDI control changes to an entity's control points and turns them
+/// into messages for it.
+///
+/// A 7-bit binding maps a CC's 0-127 onto the control value's 0.0-1.0. A
+/// 14-bit binding pairs CC `n` (0-31), the MSB, with CC `n + 32`, the LSB,
+/// following the MIDI 1.0 convention: a new MSB resets the LSB to 0, and each
+/// LSB refines the latest MSB.
+#[derive(Clone, Debug, Default)]
+pub struct MidiMap {
+    /// What each (channel, CC) sets.
+    bindings: HashMap<(u8, u8), Binding>,
+    /// The latest MSB of each 14-bit binding, by (channel, MSB CC).
+    msbs: HashMap<(u8, u8), u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Binding {
+    Coarse(usize),
+    Msb(usize),
+    Lsb(usize),
+}
+
+impl MidiMap {
+    /// Binds `cc` on `channel` (0-15) to control point `index`, replacing
+    /// whatever it was bound to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel or the CC is out of range.
+    pub fn bind(&mut self, channel: u8, cc: u8, index: usize) {
+        assert!(channel < 16 && cc < 128, "MIDI channel or CC out of range");
+        self.unbind(channel, cc);
+        self.bindings.insert((channel, cc), Binding::Coarse(index));
+    }
+
+    /// Binds `msb_cc` and `msb_cc + 32` on `channel` to control point `index`
+    /// as a 14-bit pair.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel is out of range or `msb_cc` isn't 0-31.
+    pub fn bind_14_bit(&mut self, channel: u8, msb_cc: u8, index: usize) {
+        assert!(
+            channel < 16 && msb_cc < 32,
+            "MIDI channel or MSB CC out of range"
+        );
+        self.unbind(channel, msb_cc);
+        self.unbind(channel, msb_cc + 32);
+        self.bindings.insert((channel, msb_cc), Binding::Msb(index));
+        self.bindings
+            .insert((channel, msb_cc + 32), Binding::Lsb(index));
+    }
+
+    /// Removes the binding of `cc` on `channel`, along with the other half of
+    /// a 14-bit pair.
+    pub fn unbind(&mut self, channel: u8, cc: u8) {
+        let msb_cc = match self.bindings.remove(&(channel, cc)) {
+            Some(Binding::Msb(_)) => cc,
+            Some(Binding::Lsb(_)) => cc - 32,
+            _ => return,
+        };
+        self.bindings.remove(&(channel, msb_cc));
+        self.bindings.remove(&(channel, msb_cc + 32));
+        self.msbs.remove(&(channel, msb_cc));
+    }
+
+    /// The control point that `cc` on `channel` is bound to.
+    pub fn binding(&self, channel: u8, cc: u8) -> Option<usize> {
+        match self.bindings.get(&(channel, cc))? {
+            Binding::Coarse(index) | Binding::Msb(index) | Binding::Lsb(index) => Some(*index),
+        }
+    }
+
+    /// The message for `target` that a control change sets, if the CC is
+    /// bound.
+    pub fn control_change<T: Synchronized>(
+        &mut self,
+        target: &T,
+        channel: u8,
+        cc: u8,
+        value: u8,
+    ) -> Option<T::Message> {
+        let value = value & 0x7f;
+        let (index, value) = match *self.bindings.get(&(channel, cc))? {
+            Binding::Coarse(index) => (index, control_value_7_bit(value)),
+            Binding::Msb(index) => {
+                self.msbs.insert((channel, cc), value);
+                (index, control_value_14_bit(value, 0))
+            }
+            Binding::Lsb(index) => {
+                let msb = self.msbs.get(&(channel, cc - 32)).copied();
+                (index, control_value_14_bit(msb.unwrap_or_default(), value))
+            }
+        };
+        target.message_for_index(index, value)
+    }
+
+    /// Like `control_change()`, but takes a raw three-byte MIDI message.
+    /// Messages other than control changes are ignored.
+    pub fn handle<T: Synchronized>(&mut self, target: &T, bytes: [u8; 3]) -> Option<T::Message> {
+        match bytes {
+            [status, cc, value] if status & 0xf0 == 0xb0 && cc < 0x80 => {
+                self.control_change(target, status & 0x0f, cc, value)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A 7-bit MIDI value, 0-127, as a control value, 0.0-1.0.
+pub fn control_value_7_bit(value: u8) -> F32ControlValue {
+    F32ControlValue((value & 0x7f) as f32 / 127.0)
+}
+
+/// A 14-bit MIDI value, as its MSB and LSB, as a control value, 0.0-1.0.
+pub fn control_value_14_bit(msb: u8, lsb: u8) -> F32ControlValue {
+    let value = ((msb & 0x7f) as u16) << 7 | (lsb & 0x7f) as u16;
+    F32ControlValue(value as f32 / 16383.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_7_and_14_bit_values() {
+        assert_eq!(control_value_7_bit(0).0, 0.0);
+        assert_eq!(control_value_7_bit(127).0, 1.0);
+        assert_eq!(control_value_14_bit(0x7f, 0x7f).0, 1.0);
+        assert_eq!(control_value_14_bit(0x40, 0).0, 8192.0 / 16383.0);
+    }
+
+    #[test]
+    fn unbinding_either_half_removes_a_14_bit_pair() {
+        let mut map = MidiMap::default();
+        map.bind_14_bit(0, 1, 3);
+        assert_eq!(map.binding(0, 33), Some(3));
+        map.unbind(0, 33);
+        assert_eq!(map.binding(0, 1), None);
+
+        map.bind_14_bit(0, 1, 3);
+        map.bind(0, 1, 4);
+        assert_eq!(map.binding(0, 1), Some(4));
+        assert_eq!(map.binding(0, 33), None);
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::{Merge, Stamp};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An observed-remove set, a CRDT for set-like `#[sync(merge)]` fields.
+///
+/// Each insert tags the element with a new stamp, and a removal removes only
+/// the tags it has seen, so when replicas concurrently insert and remove the
+/// same element, the insert wins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: serde::Serialize",
+        deserialize = "T: serde::Deserialize<'de>"
+    ))
+)]
+pub struct OrSet<T: Ord> {
+    /// The tags of the inserts of each element that haven't been removed.
+    #[cfg_attr(feature = "serde", serde(with = "crate::crdt::map_as_pairs"))]
+    entries: BTreeMap<T, BTreeSet<Stamp>>,
+    /// The tags of inserts that have been removed.
+    removed: BTreeSet<Stamp>,
+}
+impl<T: Ord + Clone> OrSet<T> {
+    /// Inserts `value` on behalf of `replica`, which must be unique among the
+    /// replicas that merge this set.
+    pub fn insert(&mut self, replica: u32, value: T) {
+        let time = self
+            .entries
+            .values()
+            .flatten()
+            .chain(&self.removed)
+            .map(|tag| tag.time)
+            .max()
+            .unwrap_or_default();
+        self.entries.entry(value).or_default().insert(Stamp {
+            time: time + 1,
+            replica,
+        });
+    }
+
+    /// Removes `value`. Returns whether it was present.
+    pub fn remove(&mut self, value: &T) -> bool {
+        match self.entries.remove(value) {
+            Some(tags) => {
+                self.removed.extend(tags);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.entries.contains_key(value)
+    }
+
+    /// The elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.entries.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+impl<T: Ord> Default for OrSet<T> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::default(),
+            removed: BTreeSet::default(),
+        }
+    }
+}
+impl<T: Ord + Clone> Merge for OrSet<T> {
+    fn merge(&mut self, other: &Self) {
+        self.removed.extend(&other.removed);
+        for (value, tags) in &other.entries {
+            self.entries.entry(value.clone()).or_default().extend(tags);
+        }
+        let removed = &self.removed;
+        self.entries.retain(|_, tags| {
+            tags.retain(|tag| !removed.contains(tag));
+            !tags.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_insert_beats_remove() {
+        let mut a = OrSet::default();
+        a.insert(1, "kick");
+        let mut b = a.clone();
+        b.remove(&"kick");
+        a.insert(1, "kick");
+
+        let mut merged = b.clone();
+        merged.merge(&a);
+        assert!(merged.contains(&"kick"));
+
+        // A removal that has seen every insert sticks.
+        let mut c = merged.clone();
+        c.remove(&"kick");
+        merged.merge(&c);
+        assert!(merged.is_empty());
+    }
+}
@@ -0,0 +1,314 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+//! Open Sound Control 1.0. Each control point of a `Synchronized` value has
+//! the address `/<type name>/<control path>`, like `/stuff/apple-count` or
+//! `/synth/filter/cutoff`, and an OSC message to that address with a number
+//! sets it.
+
+use crate::{DecodeError, Synchronized};
+use groove_core::control::F32ControlValue;
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+};
+
+/// An OSC argument, of one of the types that OSC 1.0 requires.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    String(String),
+    Blob(Vec<u8>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+/// What travels in a UDP datagram: a message, or a bundle of packets to
+/// apply together.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscPacket {
+    Message(OscMessage),
+    Bundle {
+        /// An NTP timestamp, where 1 means "immediately".
+        time_tag: u64,
+        packets: Vec<OscPacket>,
+    },
+}
+
+impl OscPacket {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::default();
+        self.encode_into(&mut out);
+        out
+    }
+
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Message(message) => {
+                encode_string(&message.address, out);
+                let mut type_tags = String::from(",");
+                type_tags.extend(message.args.iter().map(|arg| match arg {
+                    OscArg::Int(_) => 'i',
+                    OscArg::Float(_) => 'f',
+                    OscArg::String(_) => 's',
+                    OscArg::Blob(_) => 'b',
+                }));
+                encode_string(&type_tags, out);
+                for arg in &message.args {
+                    match arg {
+                        OscArg::Int(v) => out.extend(v.to_be_bytes()),
+                        OscArg::Float(v) => out.extend(v.to_be_bytes()),
+                        OscArg::String(v) => encode_string(v, out),
+                        OscArg::Blob(v) => {
+                            out.extend((v.len() as i32).to_be_bytes());
+                            out.extend(v);
+                            pad(out);
+                        }
+                    }
+                }
+            }
+            Self::Bundle { time_tag, packets } => {
+                encode_string("#bundle", out);
+                out.extend(time_tag.to_be_bytes());
+                for packet in packets {
+                    let start = out.len();
+                    out.extend([0; 4]);
+                    packet.encode_into(out);
+                    let size = (out.len() - start - 4) as i32;
+                    out[start..start + 4].copy_from_slice(&size.to_be_bytes());
+                }
+            }
+        }
+    }
+
+    // `usize::is_multiple_of()` needs Rust 1.87, which nothing else here does.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    pub fn decode(input: &[u8]) -> Result<Self, DecodeError> {
+        if input.len() % 4 != 0 {
+            return Err(DecodeError::Malformed);
+        }
+        let mut input = input;
+        if input.starts_with(b"#bundle\0") {
+            input = &input[8..];
+            let time_tag = u64::from_be_bytes(take(&mut input)?);
+            let mut packets = Vec::default();
+            while !input.is_empty() {
+                let size = i32::from_be_bytes(take(&mut input)?);
+                let size = usize::try_from(size).map_err(|_| DecodeError::Malformed)?;
+                if input.len() < size {
+                    return Err(DecodeError::Truncated);
+                }
+                let (packet, rest) = input.split_at(size);
+                packets.push(Self::decode(packet)?);
+                input = rest;
+            }
+            return Ok(Self::Bundle { time_tag, packets });
+        }
+
+        let address = decode_string(&mut input)?;
+        if !address.starts_with('/') {
+            return Err(DecodeError::Malformed);
+        }
+        // Very old senders leave out the type tags, and with them the
+        // arguments.
+        let type_tags = match input.is_empty() {
+            true => String::from(","),
+            false => decode_string(&mut input)?,
+        };
+        let Some(type_tags) = type_tags.strip_prefix(',') else {
+            return Err(DecodeError::Malformed);
+        };
+        let args = type_tags
+            .chars()
+            .map(|tag| match tag {
+                'i' => take(&mut input).map(|b| OscArg::Int(i32::from_be_bytes(b))),
+                'f' => take(&mut input).map(|b| OscArg::Float(f32::from_be_bytes(b))),
+                's' => decode_string(&mut input).map(OscArg::String),
+                'b' => {
+                    let size = i32::from_be_bytes(take(&mut input)?);
+                    let size = usize::try_from(size).map_err(|_| DecodeError::Malformed)?;
+                    let padded = (size + 3) & !3;
+                    if input.len() < padded {
+                        return Err(DecodeError::Truncated);
+                    }
+                    let blob = input[..size].to_vec();
+                    input = &input[padded..];
+                    Ok(OscArg::Blob(blob))
+                }
+                _ => Err(DecodeError::Malformed),
+            })
+            .collect::<Result<_, _>>()?;
+        if !input.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+        Ok(Self::Message(OscMessage { address, args }))
+    }
+}
+
+/// Pads `out` with zeros to a multiple of four bytes.
+fn pad(out: &mut Vec<u8>) {
+    out.resize((out.len() + 3) & !3, 0);
+}
+
+/// Appends an OSC-string: the bytes, a terminating zero, and padding.
+fn encode_string(s: &str, out: &mut Vec<u8>) {
+    out.extend(s.as_bytes());
+    out.push(0);
+    pad(out);
+}
+
+fn decode_string(input: &mut &[u8]) -> Result<String, DecodeError> {
+    let len = input
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(DecodeError::Truncated)?;
+    let padded = (len + 4) & !3;
+    if input.len() < padded {
+        return Err(DecodeError::Truncated);
+    }
+    let s = std::str::from_utf8(&input[..len]).map_err(|_| DecodeError::Malformed)?;
+    *input = &input[padded..];
+    Ok(s.to_string())
+}
+
+fn take<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], DecodeError> {
+    if input.len() < N {
+        return Err(DecodeError::Truncated);
+    }
+    let (bytes, rest) = input.split_at(N);
+    *input = rest;
+    Ok(bytes.try_into().unwrap())
+}
+
+/// The OSC address of control point `index` of `target`.
+pub fn osc_address<T: Synchronized>(target: &T, index: usize) -> Option<String> {
+    let path = target.control_path_for_index(index)?;
+    Some(format!("/{}/{}", T::NAME, path))
+}
+
+/// The OSC addresses of every control point of `target`, in control index
+/// order.
+pub fn osc_addresses<T: Synchronized>(target: &T) -> Vec<String> {
+    (0..target.control_index_count())
+        .filter_map(|index| osc_address(target, index))
+        .collect()
+}
+
+/// The messages that `packet` asks `target` to apply, in order, looking
+/// inside bundles. OSC messages whose address isn't one of `target`'s control
+/// points, or whose first argument isn't a number, are skipped.
+pub fn messages_from_osc<T: Synchronized>(target: &T, packet: &OscPacket) -> Vec<T::Message> {
+    match packet {
+        OscPacket::Message(message) => message_from_osc(target, message).into_iter().collect(),
+        OscPacket::Bundle { packets, .. } => packets
+            .iter()
+            .flat_map(|packet| messages_from_osc(target, packet))
+            .collect(),
+    }
+}
+
+fn message_from_osc<T: Synchronized>(target: &T, message: &OscMessage) -> Option<T::Message> {
+    let path = message
+        .address
+        .strip_prefix('/')?
+        .strip_prefix(T::NAME)?
+        .strip_prefix('/')?;
+    let value = match message.args.first()? {
+        OscArg::Float(v) => *v,
+        OscArg::Int(v) => *v as f32,
+        _ => return None,
+    };
+    target.message_for_name(path, F32ControlValue(value))
+}
+
+/// A UDP socket on the loopback interface that sends and receives OSC
+/// packets, for trying out OSC locally.
+pub struct OscListener {
+    socket: UdpSocket,
+}
+impl OscListener {
+    /// Listens on a free port of `127.0.0.1`.
+    pub fn bind_loopback() -> io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(("127.0.0.1", 0))?,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Waits at most `timeout` in `recv()`, or forever given `None`.
+    pub fn set_timeout(&self, timeout: Option<std::time::Duration>) -> io::Result<()> {
+        self.socket.set_read_timeout(timeout)
+    }
+
+    pub fn send_to(&self, packet: &OscPacket, to: SocketAddr) -> io::Result<()> {
+        self.socket.send_to(&packet.encode(), to).map(|_| ())
+    }
+
+    /// Waits for the next packet. Datagrams that aren't valid OSC are reported
+    /// as `InvalidData` errors.
+    pub fn recv(&self) -> io::Result<OscPacket> {
+        let mut buffer = [0; 65536];
+        let (len, _) = self.socket.recv_from(&mut buffer)?;
+        OscPacket::decode(&buffer[..len]).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_osc_1_0() {
+        let packet = OscPacket::Message(OscMessage {
+            address: "/oscillator/4/frequency".to_string(),
+            args: vec![OscArg::Float(440.0)],
+        });
+        // The example from the OSC 1.0 specification.
+        let bytes = b"/oscillator/4/frequency\0,f\0\0\x43\xdc\x00\x00";
+        assert_eq!(packet.encode(), bytes);
+        assert_eq!(OscPacket::decode(bytes), Ok(packet));
+
+        let packet = OscPacket::Bundle {
+            time_tag: 1,
+            packets: vec![OscPacket::Message(OscMessage {
+                address: "/a".to_string(),
+                args: vec![
+                    OscArg::Int(-1),
+                    OscArg::String("four".to_string()),
+                    OscArg::Blob(vec![1, 2, 3]),
+                ],
+            })],
+        };
+        let bytes = packet.encode();
+        assert_eq!(bytes.len() % 4, 0);
+        assert_eq!(OscPacket::decode(&bytes), Ok(packet));
+        assert_eq!(
+            OscPacket::decode(&bytes[..bytes.len() - 4]),
+            Err(DecodeError::Truncated)
+        );
+    }
+
+    #[test]
+    fn listens_on_loopback() {
+        let listener = OscListener::bind_loopback().unwrap();
+        let sender = OscListener::bind_loopback().unwrap();
+        listener
+            .set_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let packet = OscPacket::Message(OscMessage {
+            address: "/level".to_string(),
+            args: vec![OscArg::Float(0.5)],
+        });
+        sender
+            .send_to(&packet, listener.local_addr().unwrap())
+            .unwrap();
+        assert_eq!(listener.recv().unwrap(), packet);
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use std::{fmt::Debug, sync::Arc};
+
+/// Where a synchronized struct's generated `set_and_propagate_*` methods send
+/// the messages they produce, usually on their way to a remote replica. Mark
+/// the struct's field with `#[sync(outbound)]`.
+///
+/// An `Outbound` doesn't count as part of its owner's state: clones share the
+/// same sink, every `Outbound` compares equal to every other, replacing the
+/// owner with a whole-struct message keeps the owner's sink, and with the
+/// `serde` feature it serializes as a unit.
+pub struct Outbound<M> {
+    sink: Option<Arc<dyn Fn(M) + Send + Sync>>,
+}
+impl<M> Outbound<M> {
+    /// Sends future messages to `sink`, replacing any sink that was already
+    /// registered.
+    pub fn register(&mut self, sink: impl Fn(M) + Send + Sync + 'static) {
+        self.sink = Some(Arc::new(sink));
+    }
+
+    pub fn unregister(&mut self) {
+        self.sink = None;
+    }
+
+    pub fn is_registered(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Hands `message` to the registered sink. Without one, the message is
+    /// dropped.
+    pub fn send(&self, message: M) {
+        if let Some(sink) = &self.sink {
+            sink(message);
+        }
+    }
+}
+impl<M> Default for Outbound<M> {
+    fn default() -> Self {
+        Self { sink: None }
+    }
+}
+impl<M> Clone for Outbound<M> {
+    fn clone(&self) -> Self {
+        Self {
+            sink: self.sink.clone(),
+        }
+    }
+}
+impl<M> Debug for Outbound<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Outbound")
+            .field("registered", &self.is_registered())
+            .finish()
+    }
+}
+impl<M> PartialEq for Outbound<M> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+#[cfg(feature = "serde")]
+impl<M> serde::Serialize for Outbound<M> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+}
+/// A deserialized `Outbound` has no sink registered.
+#[cfg(feature = "serde")]
+impl<'de, M> serde::Deserialize<'de> for Outbound<M> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <()>::deserialize(deserializer)?;
+        Ok(Self::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Outbound;
+    use std::sync::mpsc;
+
+    #[test]
+    fn sends_only_when_registered() {
+        let (sender, receiver) = mpsc::channel();
+        let mut outbound = Outbound::default();
+
+        outbound.send(1);
+        outbound.register(move |m| sender.send(m).unwrap());
+        let clone = outbound.clone();
+        outbound.send(2);
+        clone.send(3);
+        outbound.unregister();
+        outbound.send(4);
+
+        assert!(!outbound.is_registered());
+        assert!(clone.is_registered());
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![2, 3]);
+    }
+}
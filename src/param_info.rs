@@ -0,0 +1,76 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::ControlScale;
+
+/// Describes a control point for hosts and UIs. `#[derive(Synchronization)]`
+/// generates one per control point in `Synchronized::PARAMS`, from the field's
+/// type and its `#[sync(...)]` attributes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamInfo {
+    /// The control point's name, like `apple-count`, or its full path, like
+    /// `filter/cutoff`, for a nested field's control point.
+    pub name: &'static str,
+    /// The name to show people: `#[sync(display_name = "...")]`, or else the
+    /// name in title case, like `Apple Count`.
+    pub display_name: &'static str,
+    pub value_type: ParamType,
+    /// `#[sync(default = ..)]`, in the field's own units.
+    pub default: Option<f64>,
+    /// The lowest and highest values the field takes, in its own units: its
+    /// `#[sync(range = lo..=hi)]`, or else its `#[sync(min = .., max = ..)]`.
+    pub range: Option<(f64, f64)>,
+    /// `#[sync(unit = "...")]`, like `Hz` or `dB`.
+    pub unit: Option<&'static str>,
+    /// How many steps a stepped control point takes from its lowest value to
+    /// its highest, or 0 for a continuous one. A switch has one step, and a
+    /// choice one fewer than its options.
+    pub step_count: u32,
+    /// How control values map onto the field's units, if the field has
+    /// `#[sync(min = .., max = ..)]`.
+    pub scale: Option<ControlScale>,
+    pub flags: ParamFlags,
+}
+
+/// What kind of value a control point sets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamType {
+    /// A `bool`, off below 0.5 and on above.
+    Bool,
+    Int,
+    Float,
+    /// One of several named options, like an enum's `kind`, spread evenly
+    /// over 0.0-1.0 in order.
+    Choice(&'static [&'static str]),
+    /// Any other type that converts from `F32ControlValue`.
+    Other,
+}
+
+/// Facts about a control point that hosts may want to act on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ParamFlags(u8);
+impl ParamFlags {
+    pub const NONE: Self = Self(0);
+    /// `#[sync(hidden)]`: not meant to be shown to people.
+    pub const HIDDEN: Self = Self(1);
+    /// A `#[sync(validate = "...")]` function may reject values within the
+    /// range.
+    pub const VALIDATED: Self = Self(1 << 1);
+    /// The field belongs to one of an enum's variants, so it has no value
+    /// while another variant is active.
+    pub const VARIANT: Self = Self(1 << 2);
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+impl std::ops::BitOr for ParamFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
@@ -0,0 +1,30 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::ParseLineError;
+
+/// Reads messages written as text, one per line, like `cutoff=0.5` or
+/// `oscillator/kind=square`: the path of the field, as in
+/// `Synchronized::message_path()`, then `=` and the value. A message enum's
+/// `Display` writes this form, putting a value in double quotes, with
+/// backslash escapes, when it has a line break, whitespace at either end, or
+/// a leading quote. Whitespace around the path and `=` is ignored, and so is
+/// whitespace around a value, but not inside its quotes.
+///
+/// `#[derive(Synchronization)]` implements it for message enums whose field
+/// types implement `FromStr`. Whole-value messages are written one line per
+/// field, so they're read back as field messages.
+pub trait ParseLine: Sized {
+    /// The message that sets the field at `path` to `value`, as written after
+    /// the `=`, quotes and all.
+    fn parse_field(path: &str, value: &str) -> Result<Self, ParseLineError>;
+
+    fn parse_line(line: &str) -> Result<Self, ParseLineError> {
+        let line = line.trim();
+        let (path, value) = line
+            .split_once('=')
+            .ok_or_else(|| ParseLineError::MissingValue {
+                line: line.to_string(),
+            })?;
+        Self::parse_field(path.trim(), value.trim())
+    }
+}
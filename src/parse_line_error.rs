@@ -0,0 +1,46 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use std::fmt::Display;
+
+/// Why `ParseLine::parse_line()` couldn't read a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseLineError {
+    /// The line has no `=` between the field and the value.
+    MissingValue { line: String },
+    /// No field has this path.
+    UnknownField { field: String },
+    /// The field's type couldn't parse the value.
+    InvalidValue { field: String },
+}
+impl ParseLineError {
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            Self::MissingValue { .. } => None,
+            Self::UnknownField { field } | Self::InvalidValue { field } => Some(field),
+        }
+    }
+
+    /// Prefixes the field with the name of the nested field it belongs to.
+    #[doc(hidden)]
+    pub fn nested_in(self, parent: &str) -> Self {
+        match self {
+            Self::MissingValue { .. } => self,
+            Self::UnknownField { field } => Self::UnknownField {
+                field: format!("{}/{}", parent, field),
+            },
+            Self::InvalidValue { field } => Self::InvalidValue {
+                field: format!("{}/{}", parent, field),
+            },
+        }
+    }
+}
+impl Display for ParseLineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingValue { line } => write!(f, "`{}` isn't of the form field=value", line),
+            Self::UnknownField { field } => write!(f, "unknown field `{}`", field),
+            Self::InvalidValue { field } => write!(f, "invalid value for `{}`", field),
+        }
+    }
+}
+impl std::error::Error for ParseLineError {}
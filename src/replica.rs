@@ -0,0 +1,159 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::Synchronized;
+use std::collections::BTreeMap;
+
+/// A Lamport timestamp. Stamps order by time, and the replica ID breaks ties
+/// between concurrent edits, so every replica picks the same winner.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stamp {
+    pub time: u64,
+    pub replica: u32,
+}
+
+/// A message on its way between replicas, along with when it was written.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stamped<M> {
+    pub stamp: Stamp,
+    pub message: M,
+}
+
+/// A replica of a `Synchronized` value that other replicas edit concurrently.
+///
+/// Each `#[sync]` field is a last-writer-wins register: it ends up with the
+/// value of the message with the newest stamp, whatever order messages are
+/// delivered in, and older messages that arrive late are discarded. Local
+/// edits that change more than one field, such as whole-value messages or
+/// enum variant switches, go out as one message per field, all with the same
+/// stamp.
+pub struct ReplicaState<T: Synchronized> {
+    value: T,
+    replica: u32,
+    clock: u64,
+    /// The newest message applied to each field, by path.
+    latest: BTreeMap<String, Stamped<T::Message>>,
+}
+impl<T: Synchronized> ReplicaState<T>
+where
+    T::Message: Clone,
+{
+    /// `replica` must be unique among the replicas that exchange messages,
+    /// and they must all start from the same `value`.
+    pub fn new(replica: u32, value: T) -> Self {
+        Self {
+            value,
+            replica,
+            clock: 0,
+            latest: BTreeMap::default(),
+        }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn replica(&self) -> u32 {
+        self.replica
+    }
+
+    /// Applies a local edit and returns the stamped messages to send to the
+    /// other replicas.
+    pub fn edit(&mut self, message: T::Message) -> Vec<Stamped<T::Message>> {
+        self.clock += 1;
+        let stamp = Stamp {
+            time: self.clock,
+            replica: self.replica,
+        };
+        let scope = edit_scope::<T>(&message);
+        self.value.update(message);
+        let written: Vec<_> = written_fields(&self.value, scope.as_deref())
+            .into_iter()
+            .map(|message| Stamped { stamp, message })
+            .collect();
+        for stamped in &written {
+            if let Some(path) = T::message_path(&stamped.message) {
+                self.latest.insert(path, stamped.clone());
+            }
+        }
+        written
+    }
+
+    /// Applies a message from another replica unless a newer one has already
+    /// set the same field. Returns whether it was applied.
+    pub fn receive(&mut self, stamped: Stamped<T::Message>) -> bool {
+        self.clock = self.clock.max(stamped.stamp.time);
+        let Some(path) = T::message_path(&stamped.message) else {
+            // Replicas send whole values field by field, so this one came from
+            // elsewhere and has no stamp for each field to compare against.
+            return false;
+        };
+        if self
+            .latest
+            .get(&path)
+            .is_some_and(|latest| latest.stamp >= stamped.stamp)
+        {
+            return false;
+        }
+        let switched_variant = T::is_variant_switch(&stamped.message);
+        self.value.update(stamped.message.clone());
+        self.latest.insert(path.clone(), stamped);
+
+        // A variant switch resets the enum's fields, including ones set by
+        // newer messages, or by messages that arrived while their variant
+        // wasn't active. Reapplying each field's newest message restores them.
+        if switched_variant {
+            let scope = enum_path(&path);
+            let reapply: Vec<_> = self
+                .latest
+                .iter()
+                .filter(|(p, _)| **p != path && in_scope(p, scope))
+                .map(|(_, latest)| latest.message.clone())
+                .collect();
+            for message in reapply {
+                self.value.update(message);
+            }
+        }
+        true
+    }
+}
+
+/// The path of what an edit with `message` changes, or `None` for the whole
+/// value. Switching an enum variant changes every field of the enum.
+pub(crate) fn edit_scope<T: Synchronized>(message: &T::Message) -> Option<String> {
+    let path = T::message_path(message)?;
+    if T::is_variant_switch(message) {
+        return Some(enum_path(&path).to_string());
+    }
+    Some(path)
+}
+
+/// The messages that carry the current values of the fields within `scope`,
+/// as given by `edit_scope()`.
+pub(crate) fn written_fields<T: Synchronized>(value: &T, scope: Option<&str>) -> Vec<T::Message> {
+    value
+        .to_messages()
+        .into_iter()
+        .filter(|message| match (scope, T::message_path(message)) {
+            (None, _) => true,
+            (Some(scope), Some(path)) => in_scope(&path, scope),
+            (Some(_), None) => false,
+        })
+        .collect()
+}
+
+/// The path of the enum that the variant switch at `path` belongs to: `path`
+/// without its last segment.
+fn enum_path(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+/// Whether `path` is the field at `scope` or one of its nested fields.
+fn in_scope(path: &str, scope: &str) -> bool {
+    scope.is_empty()
+        || path == scope
+        || path
+            .strip_prefix(scope)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
@@ -0,0 +1,162 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::{Merge, Stamp};
+use std::collections::BTreeMap;
+
+/// A replicated growable array, a CRDT for list-like `#[sync(merge)]` fields.
+///
+/// Each element remembers the element it was inserted after. Elements inserted
+/// after the same one by different replicas are ordered newest first, and
+/// removed elements stay behind as tombstones that later inserts can refer to,
+/// so every replica ends up with the same order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: serde::Serialize",
+        deserialize = "T: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Rga<T> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::crdt::map_as_pairs"))]
+    nodes: BTreeMap<Stamp, Node<T>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Node<T> {
+    after: Option<Stamp>,
+    value: T,
+    removed: bool,
+}
+
+impl<T: Clone> Rga<T> {
+    /// Inserts `value` at `index` on behalf of `replica`, which must be unique
+    /// among the replicas that merge this list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, replica: u32, index: usize, value: T) {
+        let after = match index {
+            0 => None,
+            _ => Some(
+                self.visible()
+                    .nth(index - 1)
+                    .expect("insertion index out of bounds"),
+            ),
+        };
+        let time = self
+            .nodes
+            .keys()
+            .map(|id| id.time)
+            .max()
+            .unwrap_or_default();
+        self.nodes.insert(
+            Stamp {
+                time: time + 1,
+                replica,
+            },
+            Node {
+                after,
+                value,
+                removed: false,
+            },
+        );
+    }
+
+    pub fn push(&mut self, replica: u32, value: T) {
+        self.insert(replica, self.len(), value);
+    }
+
+    /// Removes the element at `index` and returns it, or `None` if there's no
+    /// such element.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let id = self.visible().nth(index)?;
+        let node = self.nodes.get_mut(&id)?;
+        node.removed = true;
+        Some(node.value.clone())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.visible().map(|id| &self.nodes[&id].value)
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.visible().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The IDs of the elements that haven't been removed, in order.
+    fn visible(&self) -> impl Iterator<Item = Stamp> + '_ {
+        self.order()
+            .into_iter()
+            .filter(|id| !self.nodes[id].removed)
+    }
+
+    /// The IDs of every element, tombstones included, in order: each element
+    /// is followed by the ones inserted after it, newest first.
+    fn order(&self) -> Vec<Stamp> {
+        let mut children: BTreeMap<Option<Stamp>, Vec<Stamp>> = BTreeMap::default();
+        for (id, node) in &self.nodes {
+            children.entry(node.after).or_default().push(*id);
+        }
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = children.remove(&None).unwrap_or_default();
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            if let Some(after) = children.remove(&Some(id)) {
+                stack.extend(after);
+            }
+        }
+        order
+    }
+}
+impl<T> Default for Rga<T> {
+    fn default() -> Self {
+        Self {
+            nodes: BTreeMap::default(),
+        }
+    }
+}
+impl<T: Clone> Merge for Rga<T> {
+    fn merge(&mut self, other: &Self) {
+        for (id, node) in &other.nodes {
+            self.nodes
+                .entry(*id)
+                .and_modify(|mine| mine.removed |= node.removed)
+                .or_insert_with(|| node.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_inserts_interleave_the_same_way_everywhere() {
+        let mut a = Rga::default();
+        a.push(1, 'a');
+        a.push(1, 'd');
+        let mut b = a.clone();
+        a.insert(1, 1, 'b');
+        b.insert(2, 1, 'c');
+        b.remove(0);
+
+        let mut ab = a.clone();
+        ab.merge(&b);
+        let mut ba = b.clone();
+        ba.merge(&a);
+        assert_eq!(ab, ba);
+        assert_eq!(ab.to_vec(), vec!['c', 'b', 'd']);
+    }
+}
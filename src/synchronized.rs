@@ -0,0 +1,98 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::{ParamInfo, UpdateError};
+use groove_core::{control::F32ControlValue, traits::Controllable};
+
+/// A value that can be kept in sync with a replica by applying the messages
+/// it produces. `#[derive(Synchronization)]` implements it.
+pub trait Synchronized: Controllable {
+    /// The generated message enum, e.g., `StuffMessage` for `Stuff`.
+    type Message;
+
+    /// The type's name in kebab case, like `stuff` for `Stuff`.
+    const NAME: &'static str;
+
+    /// Describes this type's own control points, in control index order.
+    /// Those of nested fields are in the children's `PARAMS`.
+    const PARAMS: &'static [ParamInfo];
+
+    /// Applies a change produced by this value or one of its replicas. Values
+    /// outside a field's `#[sync(range = ...)]` are clamped to it, and changes
+    /// that a `#[sync(validate = "...")]` function rejects are ignored.
+    fn update(&mut self, message: Self::Message);
+
+    /// Like `update()`, but refuses out-of-range and rejected values instead,
+    /// leaving `self` as it was.
+    fn try_update(&mut self, message: Self::Message) -> Result<(), UpdateError>;
+
+    /// Like `update()`, but returns the message that undoes the change: the
+    /// field's old value, or a snapshot of the whole old value when that's
+    /// what it takes, e.g., to switch an enum back to its old variant.
+    fn update_recording(&mut self, message: Self::Message) -> Self::Message;
+
+    /// Which field `message` changes, like `cutoff` or `filter/cutoff`, or
+    /// `None` for a whole-value message. A message that replaces the nested
+    /// field `filter` as a whole has the path `filter`.
+    fn message_path(message: &Self::Message) -> Option<String>;
+
+    /// Whether `message` switches an enum to another variant, its own or a
+    /// nested field's, which resets every field of that enum. The enum's path
+    /// is the message's path without its last segment.
+    fn is_variant_switch(message: &Self::Message) -> bool;
+
+    /// Merges `theirs` into `mine` if both set the same `#[sync(merge)]`
+    /// field, directly or through nested fields, and returns whether it did.
+    /// `Crdt` keeps the newer of any other two messages for a field.
+    fn merge_messages(mine: &mut Self::Message, theirs: &Self::Message) -> bool;
+
+    /// Checks every `#[sync]` field against its range and validator.
+    fn validate(&self) -> Result<(), UpdateError>;
+
+    /// Clamps every `#[sync]` field to its range.
+    fn clamp_to_ranges(&mut self);
+
+    /// The message that sets control point `param_name` to `value`, clamped to
+    /// the field's range. Nested fields' control points are named
+    /// `<field>/<child name>`.
+    fn message_for_name(&self, param_name: &str, value: F32ControlValue) -> Option<Self::Message>;
+
+    /// The message that sets control point `param_index` to `value`, clamped
+    /// to the field's range.
+    fn message_for_index(
+        &self,
+        param_index: usize,
+        value: F32ControlValue,
+    ) -> Option<Self::Message>;
+
+    /// The message that changes the same control point as `message`, but sets
+    /// it to `value`. `None` if `message` doesn't address a control point.
+    fn parameterized_message_from_message(
+        &self,
+        message: Self::Message,
+        value: F32ControlValue,
+    ) -> Option<Self::Message>;
+
+    /// The full name of a control point, like `filter/cutoff` for a nested
+    /// field's, as `control_name_for_index()` returns it but owned.
+    fn control_path_for_index(&self, index: usize) -> Option<String>;
+
+    /// Describes control point `index`, nested fields' included. A nested
+    /// field's control point is described by the child, but named with its
+    /// full path, like `control_name_for_index()` does, e.g., `filter/cutoff`.
+    fn param_info(&self, index: usize) -> Option<ParamInfo>;
+
+    /// The message that replaces a replica with a copy of `self`, non-`#[sync]`
+    /// fields included.
+    fn snapshot_message(&self) -> Self::Message;
+
+    /// The messages that bring a replica's `#[sync]` fields up to date with
+    /// `self`, one per field, in field order. On enums, the message that
+    /// switches to the active variant comes first.
+    fn to_messages(&self) -> Vec<Self::Message>;
+
+    /// The messages that make `self` match `other`, one per changed `#[sync]`
+    /// field, in field order. On enums, a change of variant comes first.
+    /// Fields with a `#[sync(tolerance = ...)]` count as changed only when
+    /// they differ by more than that; the rest are compared with `==`.
+    fn diff(&self, other: &Self) -> Vec<Self::Message>;
+}
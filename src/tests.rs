@@ -0,0 +1,1522 @@
+use crate::{Merge, Synchronization, Synchronized};
+use groove_core::{control::F32ControlValue, traits::Controllable};
+use std::str::FromStr;
+use strum::EnumCount;
+
+mod dsp {
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct Normal(pub f32);
+    impl From<groove_core::control::F32ControlValue> for Normal {
+        fn from(value: groove_core::control::F32ControlValue) -> Self {
+            Self(value.0)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Ramp<T>(T);
+impl From<F32ControlValue> for Ramp<f32> {
+    fn from(value: F32ControlValue) -> Self {
+        Self(value.0)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+pub struct Patch {
+    uid: usize,
+
+    #[sync]
+    gain: dsp::Normal,
+    #[sync]
+    label: Option<String>,
+    #[sync]
+    steps: Vec<u8>,
+    #[sync]
+    taps: [f32; 4],
+    #[sync]
+    ramp: Ramp<f32>,
+}
+impl Patch {
+    fn set_gain(&mut self, gain: dsp::Normal) {
+        self.gain = gain;
+    }
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+    fn set_steps(&mut self, steps: Vec<u8>) {
+        self.steps = steps;
+    }
+    fn set_taps(&mut self, taps: [f32; 4]) {
+        self.taps = taps;
+    }
+    fn set_ramp(&mut self, ramp: Ramp<f32>) {
+        self.ramp = ramp;
+    }
+}
+
+#[test]
+fn non_trivial_field_types() {
+    let mut patch = Patch::default();
+
+    patch.update(PatchMessage::Label(Some("lead".to_string())));
+    patch.update(PatchMessage::Steps(vec![1, 2, 3]));
+    patch.update(PatchMessage::Taps([0.1, 0.2, 0.3, 0.4]));
+    patch.update(PatchMessage::Gain(dsp::Normal(0.5)));
+    patch.update(PatchMessage::Ramp(Ramp(0.25)));
+    assert_eq!(patch.label.as_deref(), Some("lead"));
+    assert_eq!(patch.steps, vec![1, 2, 3]);
+    assert_eq!(patch.taps, [0.1, 0.2, 0.3, 0.4]);
+    assert_eq!(patch.gain, dsp::Normal(0.5));
+    assert_eq!(patch.ramp, Ramp(0.25));
+
+    let other = Patch::default();
+    patch.update(PatchMessage::Patch(other.clone()));
+    assert_eq!(patch, other);
+
+    assert_eq!(PatchMessage::COUNT, 6);
+    assert!(matches!(
+        PatchMessage::from_str("steps"),
+        Ok(PatchMessage::Steps(_))
+    ));
+}
+
+#[test]
+fn containers_are_not_control_points() {
+    let patch = Patch::default();
+
+    assert_eq!(patch.control_index_count(), 2);
+    assert_eq!(patch.control_name_for_index(0), Some("gain"));
+    assert_eq!(patch.control_name_for_index(1), Some("ramp"));
+    assert_eq!(patch.control_name_for_index(2), None);
+
+    assert!(matches!(
+        patch.message_for_index(1, F32ControlValue(0.75)),
+        Some(PatchMessage::Ramp(Ramp(v))) if v == 0.75
+    ));
+    assert!(patch
+        .message_for_name("steps", F32ControlValue(0.75))
+        .is_none());
+    assert!(patch
+        .parameterized_message_from_message(PatchMessage::Taps([0.0; 4]), F32ControlValue(0.75))
+        .is_none());
+}
+
+type Steps = Vec<u8>;
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[sync(accessors)]
+pub struct Sequence {
+    #[sync(control = false)]
+    steps: Steps,
+    #[sync(control = false)]
+    swing: f32,
+    #[sync]
+    tempo: f32,
+}
+
+#[test]
+fn control_attribute_overrides_the_type_guess() {
+    let mut sequence = Sequence::default();
+    assert_eq!(sequence.control_index_count(), 1);
+    assert_eq!(sequence.control_name_for_index(0), Some("tempo"));
+    assert!(sequence
+        .message_for_name("swing", F32ControlValue(0.5))
+        .is_none());
+
+    sequence.update(SequenceMessage::Steps(vec![1, 2]));
+    sequence.update(SequenceMessage::Swing(0.25));
+    assert_eq!(sequence.steps(), vec![1, 2]);
+    assert_eq!(sequence.swing(), 0.25);
+}
+
+pub trait Voice: Clone + std::fmt::Debug {}
+impl Voice for f32 {}
+
+#[derive(Clone, Debug, PartialEq, Synchronization)]
+pub struct Bank<'a, T: Voice, const N: usize>
+where
+    T: PartialEq,
+{
+    name: &'a str,
+
+    #[sync]
+    voices: [T; N],
+    #[sync]
+    selected: usize,
+}
+impl<'a, T: Voice, const N: usize> Bank<'a, T, N>
+where
+    T: PartialEq,
+{
+    fn set_voices(&mut self, voices: [T; N]) {
+        self.voices = voices;
+    }
+    fn set_selected(&mut self, selected: usize) {
+        self.selected = selected;
+    }
+}
+
+#[test]
+fn generics_bounds_and_where_clauses() {
+    let name = String::from("bank");
+    let mut bank = Bank::<f32, 2> {
+        name: &name,
+        voices: [0.0, 0.0],
+        selected: 0,
+    };
+
+    bank.update(BankMessage::Voices([0.5, 0.25]));
+    if let Some(message) = bank.message_for_name("selected", 1.0.into()) {
+        bank.update(message);
+    }
+    assert_eq!(bank.voices, [0.5, 0.25]);
+    assert_eq!(bank.selected, 1);
+    assert_eq!(bank.control_index_count(), 1);
+
+    let replacement = Bank {
+        name: "other",
+        voices: [1.0, 1.0],
+        selected: 0,
+    };
+    let message: BankMessage<f32, 2> = BankMessage::Bank(replacement.clone());
+    assert_eq!(<&'static str>::from(&message), "bank");
+    bank.update(message);
+    assert_eq!(bank, replacement);
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+pub struct StereoGain(#[sync(name = "left")] f32, #[sync(name = "right")] f32);
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+pub struct Detune(#[sync] f32, usize, #[sync] f32);
+
+#[test]
+fn tuple_structs() {
+    let mut gain = StereoGain::default();
+    gain.update(StereoGainMessage::Left(0.5));
+    if let Some(message) = gain.message_for_name("right", 0.25.into()) {
+        gain.update(message);
+    }
+    assert_eq!(gain, StereoGain(0.5, 0.25));
+    assert_eq!(gain.control_name_for_index(1), Some("right"));
+
+    let mut detune = Detune::default();
+    assert_eq!(detune.control_index_count(), 2);
+    assert_eq!(detune.control_name_for_index(0), Some("field-0"));
+    assert_eq!(detune.control_name_for_index(1), Some("field-2"));
+    if let Some(message) = detune.message_for_index(1, 0.75.into()) {
+        detune.update(message);
+    }
+    detune.update(DetuneMessage::Field0(0.125));
+    assert_eq!(detune, Detune(0.125, 0, 0.75));
+
+    detune.update(DetuneMessage::Detune(Detune(1.0, 2, 3.0)));
+    assert_eq!(detune, Detune(1.0, 2, 3.0));
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", sync(serde))]
+pub enum Oscillator {
+    #[default]
+    Sine,
+    Square {
+        #[sync]
+        duty: f32,
+        phase: f32,
+    },
+    Noise(#[sync(name = "seed")] u32, #[sync] f32),
+}
+
+#[test]
+fn enums_switch_variants_and_set_active_fields() {
+    let mut osc = Oscillator::default();
+    assert_eq!(osc.kind(), OscillatorKind::Sine);
+
+    // Fields of an inactive variant are ignored.
+    osc.update(OscillatorMessage::SquareDuty(0.25));
+    assert_eq!(osc, Oscillator::Sine);
+
+    osc.update(OscillatorMessage::Kind(OscillatorKind::Square));
+    assert_eq!(
+        osc,
+        Oscillator::Square {
+            duty: 0.0,
+            phase: 0.0
+        }
+    );
+    osc.update(OscillatorMessage::SquareDuty(0.25));
+    assert_eq!(
+        osc,
+        Oscillator::Square {
+            duty: 0.25,
+            phase: 0.0
+        }
+    );
+
+    // Switching to the variant that's already active keeps its fields.
+    osc.update(OscillatorMessage::Kind(OscillatorKind::Square));
+    assert_eq!(osc.kind(), OscillatorKind::Square);
+    assert!(matches!(osc, Oscillator::Square { duty, .. } if duty == 0.25));
+
+    osc.update(OscillatorMessage::Oscillator(Oscillator::Noise(7, 0.5)));
+    osc.update(OscillatorMessage::Seed(8));
+    osc.update(OscillatorMessage::NoiseField1(0.75));
+    assert_eq!(osc, Oscillator::Noise(8, 0.75));
+}
+
+#[test]
+fn enum_control_points() {
+    let mut osc = Oscillator::default();
+    assert_eq!(osc.control_index_count(), 4);
+    assert_eq!(osc.control_name_for_index(0), Some("kind"));
+    assert_eq!(osc.control_name_for_index(1), Some("square-duty"));
+    assert_eq!(osc.control_name_for_index(3), Some("noise-field-1"));
+
+    if let Some(message) = osc.message_for_name("kind", 0.99.into()) {
+        osc.update(message);
+    }
+    assert_eq!(osc.kind(), OscillatorKind::Noise);
+    if let Some(message) = osc.message_for_index(0, 0.5.into()) {
+        osc.update(message);
+    }
+    assert_eq!(osc.kind(), OscillatorKind::Square);
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", sync(serde))]
+pub struct Filter {
+    #[sync]
+    cutoff: f32,
+    #[sync]
+    resonance: f32,
+}
+impl Filter {
+    fn set_cutoff(&mut self, cutoff: f32) {
+        self.cutoff = cutoff;
+    }
+    fn set_resonance(&mut self, resonance: f32) {
+        self.resonance = resonance;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", sync(serde))]
+pub struct Synth {
+    #[sync]
+    level: f32,
+    #[sync(nested)]
+    filter: Filter,
+    #[sync(nested)]
+    oscillator: Oscillator,
+}
+impl Synth {
+    fn set_level(&mut self, level: f32) {
+        self.level = level;
+    }
+}
+
+#[test]
+fn nested_messages_are_routed_to_children() {
+    let mut synth = Synth::default();
+    synth.update(SynthMessage::Filter(FilterMessage::Cutoff(0.5)));
+    synth.update(SynthMessage::Oscillator(OscillatorMessage::Kind(
+        OscillatorKind::Square,
+    )));
+    assert_eq!(synth.filter.cutoff, 0.5);
+    assert_eq!(synth.oscillator.kind(), OscillatorKind::Square);
+
+    synth.update(SynthMessage::Filter(FilterMessage::Filter(Filter {
+        cutoff: 0.1,
+        resonance: 0.2,
+    })));
+    assert_eq!(synth.filter.resonance, 0.2);
+
+    if let Some(message) = synth.message_for_name("filter/resonance", 0.75.into()) {
+        synth.update(message);
+    }
+    assert_eq!(synth.filter.resonance, 0.75);
+    assert!(synth
+        .message_for_name("filter/nonsense", 0.75.into())
+        .is_none());
+    assert!(synth
+        .message_for_name("nonsense/cutoff", 0.75.into())
+        .is_none());
+}
+
+#[test]
+fn nested_control_points_are_flattened() {
+    let mut synth = Synth::default();
+
+    // level, filter/cutoff, filter/resonance, oscillator/kind,
+    // oscillator/square-duty, oscillator/seed, oscillator/noise-field-1
+    assert_eq!(synth.control_index_count(), 7);
+    assert_eq!(synth.control_name_for_index(0), Some("level"));
+    assert_eq!(synth.control_name_for_index(2), Some("filter/resonance"));
+    assert_eq!(
+        synth.control_path_for_index(2).as_deref(),
+        Some("filter/resonance")
+    );
+    assert_eq!(
+        synth.control_path_for_index(4).as_deref(),
+        Some("oscillator/square-duty")
+    );
+    assert_eq!(synth.control_path_for_index(7), None);
+
+    if let Some(message) = synth.message_for_index(1, 0.25.into()) {
+        synth.update(message);
+    }
+    assert_eq!(synth.filter.cutoff, 0.25);
+    if let Some(message) = synth.message_for_index(3, 0.4.into()) {
+        synth.update(message);
+    }
+    assert_eq!(synth.oscillator.kind(), OscillatorKind::Square);
+    if let Some(message) = synth.message_for_index(4, 0.3.into()) {
+        synth.update(message);
+    }
+    assert!(matches!(synth.oscillator, Oscillator::Square { duty, .. } if duty == 0.3));
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+pub struct Crossover {
+    #[sync(nested)]
+    low: Band,
+    #[sync(nested)]
+    high: Band,
+}
+
+#[test]
+fn nested_control_names_are_full_paths() {
+    let crossover = Crossover::default();
+    let names: Vec<_> = (0..crossover.control_index_count())
+        .filter_map(|index| crossover.control_name_for_index(index))
+        .collect();
+    assert_eq!(names, ["low/frequency", "high/frequency"]);
+    assert!(matches!(
+        crossover.message_for_name(names[1], F32ControlValue(0.5)),
+        Some(CrossoverMessage::High(BandMessage::Frequency(_)))
+    ));
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+pub struct Mixer {
+    #[sync]
+    volume: f32,
+    #[sync]
+    muted: bool,
+    #[sync(outbound)]
+    outbound: crate::Outbound<MixerMessage>,
+    recalculations: usize,
+}
+impl Mixer {
+    fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        self.recalculations += 1;
+    }
+    fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+}
+
+#[test]
+fn set_and_propagate_reaches_the_replica() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut local = Mixer::default();
+    let mut remote = Mixer::default();
+    local.outbound.register(move |m| sender.send(m).unwrap());
+
+    local.set_and_propagate_volume(0.8);
+    local.set_and_propagate_muted(true);
+    assert_eq!(local.recalculations, 1);
+    for message in receiver.try_iter() {
+        remote.update(message);
+    }
+    assert_eq!(remote.volume, 0.8);
+    assert!(remote.muted);
+
+    // Replacing the whole struct keeps this replica's sink.
+    local.update(MixerMessage::Mixer(Mixer::default()));
+    assert!(local.outbound.is_registered());
+    local.set_and_propagate_volume(0.5);
+    assert!(matches!(receiver.try_recv(), Ok(MixerMessage::Volume(v)) if v == 0.5));
+}
+
+/// A field type whose own `clone()` method shadows `Clone::clone()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pan(f32);
+impl Pan {
+    #[allow(clippy::should_implement_trait)]
+    pub fn clone(&self) -> f32 {
+        self.0
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+pub struct Fader {
+    #[sync(control = false)]
+    pan: Pan,
+    #[sync(outbound)]
+    outbound: crate::Outbound<FaderMessage>,
+}
+impl Fader {
+    fn set_pan(&mut self, pan: Pan) {
+        self.pan = pan;
+    }
+}
+
+#[test]
+fn set_and_propagate_clones_with_the_clone_trait() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut fader = Fader::default();
+    fader.outbound.register(move |m| sender.send(m).unwrap());
+    fader.set_and_propagate_pan(Pan(0.25));
+    assert_eq!(Pan(0.25).clone(), 0.25);
+    assert!(matches!(receiver.try_recv(), Ok(FaderMessage::Pan(Pan(v))) if v == 0.25));
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[sync(accessors, on_change = "clear_precomputed")]
+pub struct Envelope {
+    #[sync]
+    attack: f32,
+    #[sync(on_change = "retrigger")]
+    release: f32,
+    #[sync]
+    label: String,
+    #[sync(nested)]
+    filter: Filter,
+    precomputed: Option<f32>,
+    retriggers: usize,
+}
+impl Envelope {
+    fn clear_precomputed(&mut self) {
+        self.precomputed = None;
+    }
+    fn retrigger(&mut self) {
+        self.retriggers += 1;
+    }
+}
+
+#[test]
+fn accessors_run_on_change_hooks() {
+    let mut envelope = Envelope {
+        precomputed: Some(1.0),
+        ..Default::default()
+    };
+
+    envelope.set_attack(0.25);
+    assert_eq!(envelope.attack(), 0.25);
+    assert_eq!(envelope.precomputed, None);
+
+    envelope.precomputed = Some(1.0);
+    envelope.update(EnvelopeMessage::Release(0.5));
+    assert_eq!(envelope.release(), 0.5);
+    assert_eq!(envelope.retriggers, 1);
+    assert_eq!(envelope.precomputed, Some(1.0));
+
+    envelope.update(EnvelopeMessage::Label("pluck".to_string()));
+    assert_eq!(envelope.label(), "pluck");
+    envelope.update(EnvelopeMessage::Filter(FilterMessage::Cutoff(0.5)));
+    assert_eq!(envelope.filter().cutoff, 0.5);
+}
+
+// Deriving needs no imports beyond the derive itself.
+mod track {
+    #[derive(Clone, Debug, Default, PartialEq, crate::Synchronization)]
+    #[sync(accessors, message = "TrackMsg", vis = "pub(crate)", derive(PartialEq))]
+    pub(crate) struct Track {
+        #[sync]
+        pan: f32,
+    }
+}
+
+#[test]
+fn message_enum_name_visibility_and_derives() {
+    let mut track = track::Track::default();
+    let message = track
+        .message_for_name("pan", F32ControlValue(0.75))
+        .unwrap();
+    assert_eq!(message, track::TrackMsg::Pan(0.75));
+    track.update(message);
+    assert_eq!(track.pan(), 0.75);
+    assert_eq!(track::TrackMsg::COUNT, 2);
+}
+
+/// Written once against the trait rather than for each derived type.
+fn set_every_control<T: Synchronized>(target: &mut T, value: F32ControlValue) -> Vec<String> {
+    let mut paths = Vec::default();
+    for index in 0..target.control_index_count() {
+        let message = target.message_for_index(index, value).unwrap();
+        target.update(message);
+        paths.push(target.control_path_for_index(index).unwrap());
+    }
+    paths
+}
+
+#[test]
+fn synchronized_is_usable_generically() {
+    let mut synth = Synth::default();
+    let paths = set_every_control(&mut synth, F32ControlValue(0.5));
+    assert_eq!(paths[..3], ["level", "filter/cutoff", "filter/resonance"]);
+    assert_eq!(synth.level, 0.5);
+    assert_eq!(synth.filter.resonance, 0.5);
+
+    let mut stereo = StereoGain::default();
+    assert_eq!(
+        set_every_control(&mut stereo, F32ControlValue(0.25)),
+        ["left", "right"]
+    );
+    assert_eq!(stereo, StereoGain(0.25, 0.25));
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[sync(tolerance = 0.001)]
+pub struct Meter {
+    #[sync]
+    peak: f32,
+    #[sync(tolerance = 0.5)]
+    hold: dsp::Normal,
+    #[sync]
+    label: String,
+}
+impl Meter {
+    fn set_peak(&mut self, peak: f32) {
+        self.peak = peak;
+    }
+    fn set_hold(&mut self, hold: dsp::Normal) {
+        self.hold = hold;
+    }
+    fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
+}
+impl crate::ApproxEq for dsp::Normal {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        self.0.approx_eq(&other.0, tolerance)
+    }
+}
+
+#[test]
+fn diff_skips_changes_within_tolerance() {
+    let old = Meter::default();
+    let new = Meter {
+        peak: 0.0005,
+        hold: dsp::Normal(0.25),
+        label: "L".to_string(),
+    };
+    let messages = old.diff(&new);
+    assert_eq!(messages.len(), 1);
+    assert!(matches!(&messages[0], MeterMessage::Label(label) if label == "L"));
+
+    let new = Meter {
+        peak: 0.5,
+        hold: dsp::Normal(0.75),
+        ..old.clone()
+    };
+    let mut replica = old.clone();
+    for message in old.diff(&new) {
+        replica.update(message);
+    }
+    assert_eq!(replica, new);
+    assert!(new.diff(&replica).is_empty());
+}
+
+#[test]
+fn diff_covers_nested_fields_and_enum_variants() {
+    let old = Synth::default();
+    let mut new = old.clone();
+    new.filter.resonance = 0.5;
+    new.oscillator = Oscillator::Noise(0, 0.25);
+    let messages = old.diff(&new);
+    assert!(matches!(
+        messages[..],
+        [
+            SynthMessage::Filter(FilterMessage::Resonance(_)),
+            SynthMessage::Oscillator(OscillatorMessage::Kind(OscillatorKind::Noise)),
+            SynthMessage::Oscillator(OscillatorMessage::NoiseField1(_)),
+        ]
+    ));
+    let mut replica = old.clone();
+    for message in messages {
+        replica.update(message);
+    }
+    assert_eq!(replica, new);
+}
+
+#[test]
+fn snapshots_bring_late_replicas_up_to_date() {
+    let mut synth = Synth {
+        level: 0.5,
+        oscillator: Oscillator::Square {
+            duty: 0.25,
+            phase: 0.75,
+        },
+        ..Default::default()
+    };
+    synth.filter.cutoff = 0.125;
+
+    let mut late = Synth::default();
+    late.update(synth.snapshot_message());
+    assert_eq!(late, synth);
+
+    let messages = synth.to_messages();
+    assert!(matches!(
+        messages[..],
+        [
+            SynthMessage::Level(_),
+            SynthMessage::Filter(FilterMessage::Cutoff(_)),
+            SynthMessage::Filter(FilterMessage::Resonance(_)),
+            SynthMessage::Oscillator(OscillatorMessage::Kind(OscillatorKind::Square)),
+            SynthMessage::Oscillator(OscillatorMessage::SquareDuty(_)),
+        ]
+    ));
+    let mut late = Synth::default();
+    for message in messages {
+        late.update(message);
+    }
+    // Only #[sync] fields travel this way, so the unsynchronized phase doesn't.
+    assert!(late.diff(&synth).is_empty());
+    assert_eq!(
+        late.oscillator,
+        Oscillator::Square {
+            duty: 0.25,
+            phase: 0.0
+        }
+    );
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[sync(accessors)]
+pub struct Band {
+    #[sync(range = 20.0..=20000.0)]
+    frequency: f32,
+}
+
+fn is_midi_note(note: &u8) -> bool {
+    *note < 128
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[sync(accessors)]
+pub struct Channel {
+    #[sync(range = 0.0..=1.0)]
+    gain: f32,
+    #[sync(validate = "is_midi_note")]
+    note: u8,
+    #[sync(nested)]
+    band: Band,
+}
+
+#[test]
+fn try_update_refuses_what_update_clamps_or_ignores() {
+    let mut channel = Channel::default();
+    assert_eq!(
+        channel.try_update(ChannelMessage::Gain(1.5)),
+        Err(crate::UpdateError::OutOfRange {
+            field: "gain".to_string()
+        })
+    );
+    let error = channel.try_update(ChannelMessage::Note(200)).unwrap_err();
+    assert_eq!(error.to_string(), "value for `note` was rejected");
+    let error = channel
+        .try_update(ChannelMessage::Band(BandMessage::Frequency(5.0)))
+        .unwrap_err();
+    assert_eq!(error.field(), "band/frequency");
+    assert_eq!(channel, Channel::default());
+
+    channel.update(ChannelMessage::Gain(1.5));
+    channel.update(ChannelMessage::Note(200));
+    channel.update(ChannelMessage::Band(BandMessage::Frequency(5.0)));
+    assert_eq!(channel.gain(), 1.0);
+    assert_eq!(channel.note(), 0);
+    assert_eq!(channel.band().frequency(), 20.0);
+
+    assert!(channel.try_update(ChannelMessage::Note(60)).is_ok());
+    assert_eq!(channel.note(), 60);
+}
+
+#[test]
+fn whole_value_messages_are_checked_field_by_field() {
+    let mut channel = Channel::default();
+    let mut bad = Channel::default();
+    bad.band.frequency = 30000.0;
+    assert_eq!(
+        channel.try_update(ChannelMessage::Channel(bad.clone())),
+        Err(crate::UpdateError::OutOfRange {
+            field: "band/frequency".to_string()
+        })
+    );
+    channel.update(ChannelMessage::Channel(bad));
+    assert_eq!(channel.band().frequency(), 20000.0);
+
+    bad = Channel::default();
+    bad.note = 128;
+    channel.update(ChannelMessage::Channel(bad));
+    assert_eq!(channel.band().frequency(), 20000.0);
+}
+
+#[test]
+fn control_values_are_clamped_to_ranges() {
+    let channel = Channel::default();
+    assert!(matches!(
+        channel.message_for_name("band/frequency", F32ControlValue(0.5)),
+        Some(ChannelMessage::Band(BandMessage::Frequency(f))) if f == 20.0
+    ));
+    assert!(matches!(
+        channel.message_for_name("gain", F32ControlValue(0.5)),
+        Some(ChannelMessage::Gain(g)) if g == 0.5
+    ));
+}
+
+#[test]
+fn update_recording_returns_the_inverse() {
+    let mut synth = Synth {
+        oscillator: Oscillator::Square {
+            duty: 0.5,
+            phase: 0.25,
+        },
+        ..Default::default()
+    };
+    let before = synth.clone();
+    let undo_level = synth.update_recording(SynthMessage::Level(0.75));
+    let undo_cutoff = synth.update_recording(SynthMessage::Filter(FilterMessage::Cutoff(0.125)));
+    let undo_kind = synth.update_recording(SynthMessage::Oscillator(OscillatorMessage::Kind(
+        OscillatorKind::Sine,
+    )));
+    assert!(matches!(undo_level, SynthMessage::Level(v) if v == 0.0));
+    assert!(matches!(
+        undo_cutoff,
+        SynthMessage::Filter(FilterMessage::Cutoff(v)) if v == 0.0
+    ));
+    assert_eq!(
+        Synth::message_path(&undo_cutoff).as_deref(),
+        Some("filter/cutoff")
+    );
+
+    for message in [undo_kind, undo_cutoff, undo_level] {
+        synth.update(message);
+    }
+    assert_eq!(synth, before);
+}
+
+#[test]
+fn undo_stack_groups_edits_to_the_same_field() {
+    let mut synth = Synth::default();
+    let mut history = crate::UndoStack::new(2);
+
+    for level in [0.1, 0.2, 0.3] {
+        history.apply(&mut synth, SynthMessage::Level(level));
+    }
+    history.apply(&mut synth, SynthMessage::Filter(FilterMessage::Cutoff(0.5)));
+    history.break_group();
+    history.apply(
+        &mut synth,
+        SynthMessage::Filter(FilterMessage::Cutoff(0.75)),
+    );
+    assert_eq!(synth.filter.cutoff, 0.75);
+
+    // The capacity is two steps, so the level edits are gone.
+    assert!(history.undo(&mut synth));
+    assert_eq!(synth.filter.cutoff, 0.5);
+    assert!(history.undo(&mut synth));
+    assert_eq!(synth.filter.cutoff, 0.0);
+    assert!(!history.undo(&mut synth));
+    assert_eq!(synth.level, 0.3);
+
+    assert!(history.redo(&mut synth));
+    assert!(history.redo(&mut synth));
+    assert_eq!(synth.filter.cutoff, 0.75);
+    assert!(!history.can_redo());
+
+    history.undo(&mut synth);
+    history.apply(&mut synth, SynthMessage::Level(0.9));
+    assert!(!history.can_redo());
+}
+
+/// Delivers every message to `to`, in the given order.
+fn deliver(to: &mut crate::ReplicaState<Synth>, messages: &[crate::Stamped<SynthMessage>]) {
+    for message in messages {
+        to.receive(message.clone());
+    }
+}
+
+#[test]
+fn replicas_converge_whatever_the_delivery_order() {
+    let mut ui = crate::ReplicaState::new(1, Synth::default());
+    let mut engine = crate::ReplicaState::new(2, Synth::default());
+
+    // Both edit the level at once; the engine's replica ID breaks the tie.
+    let from_ui = ui.edit(SynthMessage::Level(0.25));
+    let from_engine = engine.edit(SynthMessage::Level(0.75));
+    deliver(&mut ui, &from_engine);
+    deliver(&mut engine, &from_ui);
+    assert_eq!(ui.value().level, 0.75);
+    assert_eq!(engine.value().level, 0.75);
+
+    // A later edit beats an earlier one even when it arrives first.
+    let mut a = ui.edit(SynthMessage::Filter(FilterMessage::Cutoff(0.1)));
+    let b = ui.edit(SynthMessage::Filter(FilterMessage::Cutoff(0.2)));
+    deliver(&mut engine, &b);
+    assert!(!engine.receive(a.remove(0)));
+    assert_eq!(engine.value().filter.cutoff, 0.2);
+    assert_eq!(ui.value(), engine.value());
+}
+
+#[test]
+fn variant_switches_converge_with_reordered_fields() {
+    let start = Synth::default();
+    let mut author = crate::ReplicaState::new(1, start.clone());
+    let mut other = crate::ReplicaState::new(2, start.clone());
+    let mut switch = author.edit(SynthMessage::Oscillator(OscillatorMessage::Kind(
+        OscillatorKind::Square,
+    )));
+    let duty = author.edit(SynthMessage::Oscillator(OscillatorMessage::SquareDuty(0.3)));
+    let stale = other.edit(SynthMessage::Oscillator(OscillatorMessage::SquareDuty(0.9)));
+    switch.extend(duty);
+    switch.extend(stale);
+
+    // Every order of delivery ends in the same state.
+    let mut forward = crate::ReplicaState::new(3, start.clone());
+    let mut backward = crate::ReplicaState::new(4, start);
+    deliver(&mut forward, &switch);
+    switch.reverse();
+    deliver(&mut backward, &switch);
+    deliver(&mut author, &switch);
+    deliver(&mut other, &switch);
+    for replica in [&forward, &backward, &author, &other] {
+        assert_eq!(
+            replica.value().oscillator,
+            Oscillator::Square {
+                duty: 0.3,
+                phase: 0.0
+            }
+        );
+    }
+}
+
+#[test]
+fn whole_value_edits_go_out_field_by_field() {
+    let mut ui = crate::ReplicaState::new(1, Synth::default());
+    let mut engine = crate::ReplicaState::new(2, Synth::default());
+    let filter = Filter {
+        cutoff: 0.5,
+        ..Default::default()
+    };
+    let sent = ui.edit(SynthMessage::Filter(FilterMessage::Filter(filter.clone())));
+    assert_eq!(sent.len(), 2);
+    deliver(&mut engine, &sent);
+    assert_eq!(engine.value().filter, filter);
+    assert_eq!(ui.value(), engine.value());
+}
+
+/// A struct with a plain field that happens to be called `kind`.
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[sync(accessors)]
+pub struct Lamp {
+    #[sync]
+    kind: u8,
+    #[sync]
+    brightness: f32,
+}
+
+#[test]
+fn a_field_named_kind_is_not_a_variant_switch() {
+    let mut a = crate::ReplicaState::new(1, Lamp::default());
+    let mut b = crate::ReplicaState::new(2, Lamp::default());
+    a.edit(LampMessage::Brightness(0.1));
+    let from_a = a.edit(LampMessage::Kind(3));
+    assert_eq!(from_a.len(), 1);
+    let from_b = b.edit(LampMessage::Brightness(0.8));
+    for message in from_b {
+        a.receive(message);
+    }
+    for message in from_a {
+        b.receive(message);
+    }
+    for replica in [&a, &b] {
+        assert_eq!(
+            replica.value(),
+            &Lamp {
+                kind: 3,
+                brightness: 0.8
+            }
+        );
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", sync(serde))]
+#[sync(accessors)]
+pub struct Session {
+    #[sync]
+    tempo: f32,
+    #[sync(merge)]
+    tags: crate::OrSet<String>,
+    #[sync(merge)]
+    notes: crate::Rga<u8>,
+    #[sync(nested)]
+    synth: Synth,
+}
+
+#[test]
+fn merges_keep_concurrent_collection_edits() {
+    let mut a = crate::Crdt::new(1, Session::default());
+    let mut b = crate::Crdt::new(2, Session::default());
+    let mut tags = a.value().tags().clone();
+    tags.insert(1, "drums".to_string());
+    a.edit(SessionMessage::Tags(tags));
+    a.edit(SessionMessage::Tempo(120.0));
+    let mut tags = b.value().tags().clone();
+    tags.insert(2, "bass".to_string());
+    b.edit(SessionMessage::Tags(tags));
+    b.edit(SessionMessage::Tempo(90.0));
+
+    a.merge(&b);
+    b.merge(&a);
+    assert_eq!(a.value(), b.value());
+    assert_eq!(a.value().tempo(), 90.0);
+    assert_eq!(
+        a.value().tags().iter().collect::<Vec<_>>(),
+        vec!["bass", "drums"]
+    );
+}
+
+#[test]
+fn crdts_keep_newer_fields_beside_a_field_named_kind() {
+    let mut a = crate::Crdt::new(1, Lamp::default());
+    let mut b = crate::Crdt::new(2, Lamp::default());
+    a.edit(LampMessage::Brightness(0.1));
+    a.edit(LampMessage::Kind(3));
+    b.edit(LampMessage::Brightness(0.8));
+
+    a.merge(&b);
+    b.merge(&a);
+    assert_eq!(a.value(), b.value());
+    assert_eq!(
+        a.value(),
+        &Lamp {
+            kind: 3,
+            brightness: 0.8
+        }
+    );
+}
+
+/// Makes a random edit like a user of replica `crdt` might.
+fn random_edit(crdt: &mut crate::Crdt<Session>, rng: &mut impl rand::Rng) {
+    let replica = crdt.replica();
+    let message = match rng.gen_range(0..7) {
+        0 => SessionMessage::Tempo(rng.gen_range(60..180) as f32),
+        1 => {
+            let mut tags = crdt.value().tags().clone();
+            let tag = ["drums", "bass", "keys"][rng.gen_range(0..3)].to_string();
+            if !tags.remove(&tag) {
+                tags.insert(replica, tag);
+            }
+            SessionMessage::Tags(tags)
+        }
+        2 => {
+            let mut notes = crdt.value().notes().clone();
+            match notes.len() {
+                len if len > 0 && rng.gen_bool(0.3) => {
+                    notes.remove(rng.gen_range(0..len));
+                }
+                len => notes.insert(replica, rng.gen_range(0..=len), rng.gen()),
+            }
+            SessionMessage::Notes(notes)
+        }
+        3 => SessionMessage::Synth(SynthMessage::Level(rng.gen())),
+        4 => SessionMessage::Synth(SynthMessage::Oscillator(OscillatorMessage::Kind(
+            [
+                OscillatorKind::Sine,
+                OscillatorKind::Square,
+                OscillatorKind::Noise,
+            ][rng.gen_range(0..3)],
+        ))),
+        5 => SessionMessage::Synth(SynthMessage::Oscillator(OscillatorMessage::SquareDuty(
+            rng.gen(),
+        ))),
+        _ => SessionMessage::Synth(SynthMessage::Filter(FilterMessage::Cutoff(rng.gen()))),
+    };
+    crdt.edit(message);
+}
+
+#[test]
+fn merges_converge_in_any_order() {
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(17);
+    for _ in 0..50 {
+        // Replicas edit offline, occasionally syncing with one another.
+        let mut replicas: Vec<_> = (0..4)
+            .map(|replica| crate::Crdt::new(replica, Session::default()))
+            .collect();
+        for _ in 0..30 {
+            let i = rng.gen_range(0..replicas.len());
+            if rng.gen_bool(0.2) {
+                let other = replicas[rng.gen_range(0..replicas.len())].clone();
+                replicas[i].merge(&other);
+            } else {
+                random_edit(&mut replicas[i], &mut rng);
+            }
+        }
+
+        // Merging everything, in any order and grouping, and with repeats,
+        // gives the same result.
+        let mut results = Vec::default();
+        for _ in 0..4 {
+            let mut order = replicas.clone();
+            order.shuffle(&mut rng);
+            order.push(order[rng.gen_range(0..order.len())].clone());
+            let mut merged = order.remove(0);
+            for other in &order {
+                merged.merge(other);
+            }
+            results.push(merged);
+        }
+        let (left, right) = replicas.split_at(2);
+        let mut paired = left[0].clone();
+        paired.merge(&left[1]);
+        let mut other_pair = right[0].clone();
+        other_pair.merge(&right[1]);
+        paired.merge(&other_pair);
+        results.push(paired);
+
+        for merged in &results {
+            assert_eq!(merged.value(), results[0].value());
+            let mut again = merged.clone();
+            again.merge(merged);
+            assert_eq!(again.value(), merged.value());
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn messages_serialize_with_their_kebab_case_names() {
+    use serde_json::json;
+
+    let message = SynthMessage::Oscillator(OscillatorMessage::NoiseField1(0.5));
+    assert_eq!(
+        serde_json::to_value(&message).unwrap(),
+        json!({ "oscillator": { "noise-field-1": 0.5 } })
+    );
+    let message = OscillatorMessage::Kind(OscillatorKind::Square);
+    assert_eq!(<&str>::from(&message), "kind");
+    assert_eq!(
+        serde_json::to_value(&message).unwrap(),
+        json!({ "kind": "square" })
+    );
+
+    let mut session = Session::default();
+    session.tags.insert(1, "drums".to_string());
+    session.notes.push(1, 60);
+    session.synth.oscillator = Oscillator::Square {
+        duty: 0.25,
+        phase: 0.0,
+    };
+    let json = serde_json::to_string(&session.snapshot_message()).unwrap();
+    let mut replica = Session::default();
+    replica.update(serde_json::from_str(&json).unwrap());
+    assert_eq!(replica, session);
+}
+
+/// `message` encoded with `Wire`.
+fn encoded<M: crate::Wire>(message: &M) -> Vec<u8> {
+    let mut out = Vec::default();
+    message.encode(&mut out);
+    out
+}
+
+#[test]
+fn messages_round_trip_through_the_wire_codec() {
+    use crate::Wire;
+
+    // level is control point 0, the nested fields follow, and the square
+    // duty is the oscillator's control point 1.
+    assert_eq!(encoded(&SynthMessage::Level(0.5)), [0, 0, 0, 0, 0x3f]);
+    let message = SynthMessage::Oscillator(OscillatorMessage::SquareDuty(0.5));
+    assert_eq!(encoded(&message)[..2], [2, 1]);
+
+    let synth = Synth {
+        level: 0.75,
+        filter: Filter {
+            cutoff: 0.25,
+            ..Default::default()
+        },
+        oscillator: Oscillator::Noise(7, 0.5),
+    };
+    let mut messages = synth.to_messages();
+    messages.push(synth.snapshot_message());
+    for message in messages {
+        let decoded = SynthMessage::decode(&encoded(&message)).unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", message));
+    }
+}
+
+#[test]
+fn decoding_refuses_truncated_and_unknown_input() {
+    use crate::{DecodeError, Wire};
+
+    let synth = Synth {
+        oscillator: Oscillator::Square {
+            duty: 0.25,
+            phase: 0.0,
+        },
+        ..Default::default()
+    };
+    let bytes = encoded(&synth.snapshot_message());
+    for len in 0..bytes.len() {
+        assert_eq!(
+            SynthMessage::decode(&bytes[..len]).unwrap_err(),
+            DecodeError::Truncated
+        );
+    }
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert_eq!(
+        SynthMessage::decode(&longer).unwrap_err(),
+        DecodeError::TrailingBytes
+    );
+    assert_eq!(
+        SynthMessage::decode(&[42]).unwrap_err(),
+        DecodeError::UnknownTag(42)
+    );
+    assert_eq!(
+        OscillatorMessage::decode(&[0, 3]).unwrap_err(),
+        DecodeError::Malformed
+    );
+}
+
+#[test]
+fn decoding_refuses_whole_values_nested_in_whole_values() {
+    use crate::{DecodeError, Wire};
+
+    // Tag 2 is the whole filter, followed by one message, which is another
+    // whole filter, and so on far deeper than the stack would allow.
+    let deep = [2, 1].repeat(200_000);
+    assert_eq!(
+        FilterMessage::decode(&deep).unwrap_err(),
+        DecodeError::Malformed
+    );
+}
+
+#[test]
+fn messages_read_and_write_as_text() {
+    use crate::{ParseLine, ParseLineError};
+
+    assert_eq!(SynthMessage::Level(0.5).to_string(), "level=0.5");
+    let message = SynthMessage::Oscillator(OscillatorMessage::Kind(OscillatorKind::Square));
+    assert_eq!(message.to_string(), "oscillator/kind=square");
+    assert!(matches!(
+        SynthMessage::parse_line(" oscillator/kind = square\n"),
+        Ok(SynthMessage::Oscillator(OscillatorMessage::Kind(
+            OscillatorKind::Square
+        )))
+    ));
+
+    let synth = Synth {
+        level: 0.75,
+        filter: Filter {
+            cutoff: 0.25,
+            resonance: 0.5,
+        },
+        oscillator: Oscillator::Noise(7, 0.5),
+    };
+    let text = synth.snapshot_message().to_string();
+    assert_eq!(
+        text,
+        "level=0.75\n\
+         filter/cutoff=0.25\n\
+         filter/resonance=0.5\n\
+         oscillator/kind=noise\n\
+         oscillator/seed=7\n\
+         oscillator/noise-field-1=0.5"
+    );
+    let mut replica = Synth::default();
+    for line in text.lines() {
+        replica.update(SynthMessage::parse_line(line).unwrap());
+    }
+    assert_eq!(replica, synth);
+
+    for (line, error) in [
+        (
+            "level",
+            ParseLineError::MissingValue {
+                line: "level".to_string(),
+            },
+        ),
+        (
+            "filter/q=1",
+            ParseLineError::UnknownField {
+                field: "filter/q".to_string(),
+            },
+        ),
+        (
+            "oscillator/kind=saw",
+            ParseLineError::InvalidValue {
+                field: "oscillator/kind".to_string(),
+            },
+        ),
+    ] {
+        assert_eq!(SynthMessage::parse_line(line).unwrap_err(), error);
+    }
+}
+
+#[test]
+fn messages_display_without_displayable_field_types() {
+    assert_eq!(PatchMessage::Steps(vec![1, 2]).to_string(), "steps");
+    assert_eq!(PatchMessage::Label(None).to_string(), "label");
+    assert_eq!(PatchMessage::Taps([0.0; 4]).to_string(), "taps");
+    assert_eq!(PatchMessage::Gain(dsp::Normal(0.5)).to_string(), "gain");
+}
+
+#[test]
+fn text_lines_keep_awkward_string_values() {
+    use crate::ParseLine;
+
+    for label in ["line1\nlevel=0.9", "a=b", "  two  words ", "\"quoted\""] {
+        let line = EnvelopeMessage::Label(label.to_string()).to_string();
+        assert_eq!(line.lines().count(), 1);
+        assert!(matches!(
+            EnvelopeMessage::parse_line(&line),
+            Ok(EnvelopeMessage::Label(l)) if l == label
+        ));
+    }
+    assert_eq!(
+        EnvelopeMessage::Label(" x".to_string()).to_string(),
+        "label=\" x\""
+    );
+}
+
+#[test]
+fn osc_messages_set_control_points() {
+    use crate::osc::{self, OscArg, OscMessage, OscPacket};
+
+    let synth = Synth::default();
+    assert_eq!(Synth::NAME, "synth");
+    assert_eq!(
+        osc::osc_addresses(&synth)[..3],
+        [
+            "/synth/level",
+            "/synth/filter/cutoff",
+            "/synth/filter/resonance"
+        ]
+    );
+
+    let packet = OscPacket::Bundle {
+        time_tag: 1,
+        packets: vec![
+            OscPacket::Message(OscMessage {
+                address: "/synth/filter/cutoff".to_string(),
+                args: vec![OscArg::Float(0.25)],
+            }),
+            OscPacket::Message(OscMessage {
+                address: "/mixer/volume".to_string(),
+                args: vec![OscArg::Float(0.5)],
+            }),
+            OscPacket::Message(OscMessage {
+                address: "/synth/level".to_string(),
+                args: vec![OscArg::String("loud".to_string())],
+            }),
+        ],
+    };
+    let packet = OscPacket::decode(&packet.encode()).unwrap();
+    let messages = osc::messages_from_osc(&synth, &packet);
+    assert_eq!(messages.len(), 1);
+    assert!(matches!(
+        messages[0],
+        SynthMessage::Filter(FilterMessage::Cutoff(v)) if v == 0.25
+    ));
+}
+
+#[test]
+fn midi_control_changes_set_control_points() {
+    let synth = Synth::default();
+    let mut map = crate::MidiMap::default();
+    map.bind(0, 7, 0);
+    map.bind_14_bit(2, 1, 1);
+
+    assert!(matches!(
+        map.handle(&synth, [0xb0, 7, 127]),
+        Some(SynthMessage::Level(v)) if v == 1.0
+    ));
+    assert!(map.handle(&synth, [0xb1, 7, 127]).is_none());
+    assert!(map.handle(&synth, [0x90, 7, 127]).is_none());
+
+    // The MSB alone, then refined by the LSB.
+    assert!(matches!(
+        map.handle(&synth, [0xb2, 1, 0x40]),
+        Some(SynthMessage::Filter(FilterMessage::Cutoff(v))) if v == 8192.0 / 16383.0
+    ));
+    assert!(matches!(
+        map.handle(&synth, [0xb2, 33, 0x7f]),
+        Some(SynthMessage::Filter(FilterMessage::Cutoff(v))) if v == 8319.0 / 16383.0
+    ));
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[sync(accessors)]
+pub struct Orchard {
+    #[sync(min = 0, max = 100, default = 10)]
+    apple_count: usize,
+    #[sync(min = 20.0, max = 20000.0, curve = "log", unit = "Hz")]
+    frequency: f32,
+    #[sync(min = 0.0, max = 1.0, step = 0.25, range = 0.0..=0.5)]
+    #[sync(display_name = "Grade", hidden)]
+    quality: f64,
+}
+
+#[test]
+fn control_values_are_scaled_to_real_units() {
+    let orchard = Orchard::default();
+    assert!(matches!(
+        orchard.message_for_name("apple-count", F32ControlValue(0.5)),
+        Some(OrchardMessage::AppleCount(50))
+    ));
+    assert!(matches!(
+        orchard.message_for_name("frequency", F32ControlValue(0.5)),
+        Some(OrchardMessage::Frequency(v)) if (v - 632.456).abs() < 0.001
+    ));
+    assert!(matches!(
+        orchard.message_for_index(2, F32ControlValue(0.3)),
+        Some(OrchardMessage::Quality(v)) if v == 0.25
+    ));
+    // Stepped to 1.0, then clamped to the range.
+    assert!(matches!(
+        orchard.message_for_index(2, F32ControlValue(0.9)),
+        Some(OrchardMessage::Quality(v)) if v == 0.5
+    ));
+}
+
+#[test]
+fn control_values_read_back_current_state() {
+    use crate::ControlValues;
+
+    let mut synth = Synth::default();
+    synth.update(SynthMessage::Level(0.75));
+    synth.update(SynthMessage::Oscillator(OscillatorMessage::Oscillator(
+        Oscillator::Square {
+            duty: 0.25,
+            phase: 0.0,
+        },
+    )));
+    assert_eq!(synth.control_value_for_index(0).map(|v| v.0), Some(0.75));
+    assert_eq!(
+        synth
+            .control_value_for_name("oscillator/square-duty")
+            .map(|v| v.0),
+        Some(0.25)
+    );
+    // Fields of inactive variants and unknown names have no value.
+    assert!(synth.control_value_for_index(5).is_none());
+    assert!(synth.control_value_for_name("filter/nonsense").is_none());
+    assert!(synth.control_value_for_index(7).is_none());
+
+    // Every control value turns back into the same message.
+    for index in 0..synth.control_index_count() {
+        if let Some(value) = synth.control_value_for_index(index) {
+            let mut replica = synth.clone();
+            replica.update(synth.message_for_index(index, value).unwrap());
+            assert_eq!(replica, synth);
+        }
+    }
+
+    let mut orchard = Orchard::default();
+    orchard.set_apple_count(25);
+    orchard.set_frequency(632.456);
+    orchard.set_quality(0.5);
+    assert_eq!(
+        orchard.control_value_for_name("apple-count").map(|v| v.0),
+        Some(0.25)
+    );
+    assert!(orchard
+        .control_value_for_index(1)
+        .is_some_and(|v| (v.0 - 0.5).abs() < 1e-6));
+    assert_eq!(orchard.control_value_for_index(2).map(|v| v.0), Some(0.5));
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Synchronization)]
+#[sync(accessors)]
+pub struct Keyboard {
+    #[sync(range = 21..=108)]
+    lowest_note: u8,
+}
+
+#[test]
+fn params_describe_every_control_point() {
+    use crate::{ControlScale, Curve, ParamFlags, ParamInfo, ParamType};
+
+    assert_eq!(
+        Orchard::PARAMS[0],
+        ParamInfo {
+            name: "apple-count",
+            display_name: "Apple Count",
+            value_type: ParamType::Int,
+            default: Some(10.0),
+            range: Some((0.0, 100.0)),
+            unit: None,
+            step_count: 100,
+            scale: Some(ControlScale {
+                min: 0.0,
+                max: 100.0,
+                curve: Curve::Linear,
+                step: None,
+            }),
+            flags: ParamFlags::NONE,
+        }
+    );
+    assert_eq!(Orchard::PARAMS[1].unit, Some("Hz"));
+    assert_eq!(Orchard::PARAMS[1].step_count, 0);
+    // The range narrows the scale.
+    let quality = &Orchard::PARAMS[2];
+    assert_eq!(quality.display_name, "Grade");
+    assert_eq!(quality.range, Some((0.0, 0.5)));
+    assert_eq!(quality.step_count, 2);
+    assert!(quality.flags.contains(ParamFlags::HIDDEN));
+
+    // Integers move one at a time over their range, scaled or not.
+    assert_eq!(Keyboard::PARAMS[0].range, Some((21.0, 108.0)));
+    assert_eq!(Keyboard::PARAMS[0].step_count, 87);
+
+    let channel = Channel::default();
+    assert_eq!(Channel::PARAMS.len(), 2);
+    assert!(Channel::PARAMS[1].flags.contains(ParamFlags::VALIDATED));
+    assert_eq!(
+        channel.param_info(2).map(|p| p.range),
+        Some(Some((20.0, 20000.0)))
+    );
+
+    // Nested fields' control points are described by the children, and
+    // named with their full paths.
+    let synth = Synth::default();
+    for index in 0..synth.control_index_count() {
+        assert_eq!(
+            synth.param_info(index).map(|p| p.name),
+            synth.control_name_for_index(index)
+        );
+    }
+    let crossover = Crossover::default();
+    assert_eq!(
+        crossover.param_info(0).map(|p| p.name),
+        Some("low/frequency")
+    );
+    assert_eq!(
+        crossover.param_info(1).map(|p| p.name),
+        Some("high/frequency")
+    );
+    assert!(synth.param_info(synth.control_index_count()).is_none());
+    let kind = synth.param_info(3).unwrap();
+    assert_eq!(
+        kind.value_type,
+        ParamType::Choice(&["sine", "square", "noise"])
+    );
+    assert_eq!(kind.step_count, 2);
+    assert!(synth
+        .param_info(4)
+        .unwrap()
+        .flags
+        .contains(ParamFlags::VARIANT));
+}
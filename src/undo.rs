@@ -0,0 +1,109 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::Synchronized;
+use std::collections::VecDeque;
+
+/// One step of history: the message that reverses it, and the field it
+/// changed, for grouping.
+struct Step<M> {
+    message: M,
+    path: Option<String>,
+}
+
+/// Undo and redo history for a `Synchronized` value, kept as the messages
+/// that reverse each change.
+///
+/// Consecutive edits to the same field, such as the stream of messages from a
+/// knob being turned, are grouped into one step, until `break_group()`, an
+/// undo or a redo ends the group. Once there are `capacity` steps, the oldest
+/// is forgotten.
+pub struct UndoStack<T: Synchronized> {
+    undo: VecDeque<Step<T::Message>>,
+    redo: Vec<Step<T::Message>>,
+    capacity: usize,
+    grouping: bool,
+}
+impl<T: Synchronized> UndoStack<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo: VecDeque::default(),
+            redo: Vec::default(),
+            capacity,
+            grouping: false,
+        }
+    }
+
+    /// Applies `message` to `target` as a new step of history, which discards
+    /// anything that could have been redone.
+    pub fn apply(&mut self, target: &mut T, message: T::Message) {
+        let path = T::message_path(&message);
+        let inverse = target.update_recording(message);
+        self.redo.clear();
+        let grouped = self.grouping
+            && path.is_some()
+            && self.undo.back().is_some_and(|step| step.path == path);
+        if !grouped {
+            // The first step of a group is the one that remembers the value
+            // from before the group.
+            self.undo.push_back(Step {
+                message: inverse,
+                path,
+            });
+            if self.undo.len() > self.capacity {
+                self.undo.pop_front();
+            }
+        }
+        self.grouping = true;
+    }
+
+    /// Reverses the latest step. Returns false if there was nothing to undo.
+    pub fn undo(&mut self, target: &mut T) -> bool {
+        self.grouping = false;
+        match self.undo.pop_back() {
+            Some(step) => {
+                self.redo.push(Step {
+                    message: target.update_recording(step.message),
+                    path: step.path,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reapplies the latest undone step. Returns false if there was nothing to
+    /// redo.
+    pub fn redo(&mut self, target: &mut T) -> bool {
+        self.grouping = false;
+        match self.redo.pop() {
+            Some(step) => {
+                self.undo.push_back(Step {
+                    message: target.update_recording(step.message),
+                    path: step.path,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes the next `apply()` start a new step even if it changes the same
+    /// field as the last one, e.g., when the user lets go of a knob.
+    pub fn break_group(&mut self) {
+        self.grouping = false;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.grouping = false;
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use std::fmt::Display;
+
+/// Why `Synchronized::try_update()` refused a message. `field` is the control
+/// name of the field that refused it, like `cutoff`, or its full path, like
+/// `filter/cutoff`, when a nested child refused it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UpdateError {
+    /// The value was outside the field's `#[sync(range = ...)]`.
+    OutOfRange { field: String },
+    /// The field's `#[sync(validate = "...")]` function rejected the value.
+    Rejected { field: String },
+}
+impl UpdateError {
+    pub fn field(&self) -> &str {
+        match self {
+            Self::OutOfRange { field } | Self::Rejected { field } => field,
+        }
+    }
+
+    /// Prefixes the field with the name of the nested field it belongs to.
+    #[doc(hidden)]
+    pub fn nested_in(self, parent: &str) -> Self {
+        match self {
+            Self::OutOfRange { field } => Self::OutOfRange {
+                field: format!("{}/{}", parent, field),
+            },
+            Self::Rejected { field } => Self::Rejected {
+                field: format!("{}/{}", parent, field),
+            },
+        }
+    }
+}
+impl Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfRange { field } => write!(f, "value for `{}` is out of range", field),
+            Self::Rejected { field } => write!(f, "value for `{}` was rejected", field),
+        }
+    }
+}
+impl std::error::Error for UpdateError {}
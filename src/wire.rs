@@ -0,0 +1,238 @@
+// Copyright (c) 2023 Mike Tsao. All rights reserved.
+
+use crate::DecodeError;
+
+/// A compact binary encoding. `#[derive(Synchronization)]` implements it for
+/// message enums whose field types implement it, encoding each message as a
+/// varint tag followed by the value.
+///
+/// Unsigned integers are LEB128 varints, signed ones are zigzag varints,
+/// floats are little-endian, and strings and `Vec`s are prefixed with their
+/// length.
+pub trait Wire: Sized {
+    /// Appends the encoded value to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value from the front of `input` and advances `input` past
+    /// it.
+    fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError>;
+
+    /// Decodes a value that takes up all of `input`.
+    fn decode(input: &[u8]) -> Result<Self, DecodeError> {
+        let mut rest = input;
+        let value = Self::decode_from(&mut rest)?;
+        if !rest.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+        Ok(value)
+    }
+}
+
+/// Takes the next `N` bytes off the front of `input`.
+fn take<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], DecodeError> {
+    if input.len() < N {
+        return Err(DecodeError::Truncated);
+    }
+    let (bytes, rest) = input.split_at(N);
+    *input = rest;
+    Ok(bytes.try_into().unwrap())
+}
+
+impl Wire for u64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let mut v = *self;
+        while v >= 0x80 {
+            out.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        out.push(v as u8);
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let [byte] = take(input)?;
+            let bits = (byte & 0x7f) as u64;
+            if bits << shift >> shift != bits {
+                return Err(DecodeError::Malformed);
+            }
+            v |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(DecodeError::Malformed)
+    }
+}
+impl Wire for i64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (((*self << 1) ^ (*self >> 63)) as u64).encode(out)
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let v = u64::decode_from(input)?;
+        Ok((v >> 1) as i64 ^ -((v & 1) as i64))
+    }
+}
+
+macro_rules! impl_wire_via {
+    ($wide:ty: $($ty:ty),*) => {
+        $(
+            impl Wire for $ty {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    (*self as $wide).encode(out)
+                }
+
+                fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError> {
+                    <$ty>::try_from(<$wide>::decode_from(input)?)
+                        .map_err(|_| DecodeError::Malformed)
+                }
+            }
+        )*
+    };
+}
+impl_wire_via!(u64: u8, u16, u32, usize);
+impl_wire_via!(i64: i8, i16, i32, isize);
+
+macro_rules! impl_wire_le {
+    ($($ty:ty),*) => {
+        $(
+            impl Wire for $ty {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend(self.to_le_bytes())
+                }
+
+                fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError> {
+                    take(input).map(<$ty>::from_le_bytes)
+                }
+            }
+        )*
+    };
+}
+impl_wire_le!(f32, f64);
+
+impl Wire for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8)
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        match take(input)? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(DecodeError::Malformed),
+        }
+    }
+}
+impl Wire for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        out.extend(self.as_bytes())
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let len = usize::decode_from(input)?;
+        if input.len() < len {
+            return Err(DecodeError::Truncated);
+        }
+        let (bytes, rest) = input.split_at(len);
+        *input = rest;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::Malformed)
+    }
+}
+impl<T: Wire> Wire for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.is_some().encode(out);
+        if let Some(v) = self {
+            v.encode(out)
+        }
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        match bool::decode_from(input)? {
+            true => T::decode_from(input).map(Some),
+            false => Ok(None),
+        }
+    }
+}
+impl<T: Wire> Wire for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for v in self {
+            v.encode(out)
+        }
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        // Doesn't reserve `len` up front, since that comes from the input.
+        let len = usize::decode_from(input)?;
+        // Zero-sized elements take no input, so nothing else would stop a
+        // huge length from looping that many times. Other elements take at
+        // least a byte each, so no real length is longer than the input.
+        if std::mem::size_of::<T>() == 0 && len > input.len() {
+            return Err(DecodeError::Malformed);
+        }
+        (0..len).map(|_| T::decode_from(input)).collect()
+    }
+}
+impl<T: Wire, const N: usize> Wire for [T; N] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        for v in self {
+            v.encode(out)
+        }
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let values: Vec<T> = (0..N)
+            .map(|_| T::decode_from(input))
+            .collect::<Result<_, _>>()?;
+        Ok(values.try_into().ok().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Wire;
+    use crate::DecodeError;
+
+    fn encoded<T: Wire>(value: T) -> Vec<u8> {
+        let mut out = Vec::default();
+        value.encode(&mut out);
+        out
+    }
+
+    #[test]
+    fn integers_are_varints() {
+        assert_eq!(encoded(1u32), [1]);
+        assert_eq!(encoded(300u32), [0xac, 0x02]);
+        assert_eq!(encoded(-1i32), [1]);
+        assert_eq!(encoded(1i32), [2]);
+        assert_eq!(u64::decode(&encoded(u64::MAX)), Ok(u64::MAX));
+        assert_eq!(i64::decode(&encoded(i64::MIN)), Ok(i64::MIN));
+        assert_eq!(u8::decode(&encoded(256u32)), Err(DecodeError::Malformed));
+        assert_eq!(u64::decode(&[0xff; 11]), Err(DecodeError::Malformed));
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let value = (
+            Some("lead".to_string()),
+            vec![[0.5f32, -1.0], [0.0, 2.0]],
+            true,
+        );
+        assert_eq!(Option::decode(&encoded(value.0.clone())), Ok(value.0));
+        assert_eq!(Vec::decode(&encoded(value.1.clone())), Ok(value.1));
+        assert_eq!(bool::decode(&encoded(value.2)), Ok(value.2));
+        assert_eq!(bool::decode(&[2]), Err(DecodeError::Malformed));
+        assert_eq!(bool::decode(&[1, 0]), Err(DecodeError::TrailingBytes));
+        assert_eq!(String::decode(&[3, b'a']), Err(DecodeError::Truncated));
+    }
+
+    #[test]
+    fn zero_sized_elements_cant_outnumber_the_input() {
+        assert_eq!(
+            Vec::<[u8; 0]>::decode(&encoded(u64::MAX)),
+            Err(DecodeError::Malformed)
+        );
+    }
+}